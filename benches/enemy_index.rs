@@ -0,0 +1,141 @@
+use bevy::prelude::*;
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use fishmans_adventure::spatial_index::EnemyIndex;
+use kd_tree::{KdPoint, KdTree};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+const ENEMY_COUNTS: [usize; 3] = [100, 1000, 5000];
+const QUERY_RADIUS: f32 = 150.0;
+const WORLD_SIZE: f32 = 3000.0;
+
+struct Point {
+    pos: Vec2,
+    entity: Entity,
+}
+
+impl KdPoint for Point {
+    type Scalar = f32;
+    type Dim = typenum::U2;
+    fn at(&self, k: usize) -> f32 {
+        if k == 0 {
+            return self.pos.x;
+        }
+
+        self.pos.y
+    }
+}
+
+fn random_positions(count: usize, rng: &mut StdRng) -> Vec<Vec2> {
+    (0..count)
+        .map(|_| {
+            Vec2::new(
+                rng.gen_range(-WORLD_SIZE..WORLD_SIZE),
+                rng.gen_range(-WORLD_SIZE..WORLD_SIZE),
+            )
+        })
+        .collect()
+}
+
+/// One simulation tick: every enemy moves a little, then 100 radius queries
+/// are made, roughly what bullets and healers ask for each frame.
+fn bench_tick(c: &mut Criterion) {
+    let mut group = c.benchmark_group("tick");
+    for count in ENEMY_COUNTS {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut positions = random_positions(count, &mut rng);
+        let queries = random_positions(100, &mut rng);
+        let steps = (0..count)
+            .map(|_| Vec2::new(rng.gen_range(-2.0..2.0), rng.gen_range(-2.0..2.0)))
+            .collect::<Vec<_>>();
+
+        group.bench_with_input(
+            BenchmarkId::new("kd_tree_rebuild", count),
+            &count,
+            |b, _| {
+                b.iter(|| {
+                    for (pos, step) in positions.iter_mut().zip(&steps) {
+                        *pos += *step;
+                    }
+                    let tree = KdTree::build_by_ordered_float(
+                        positions
+                            .iter()
+                            .enumerate()
+                            .map(|(i, pos)| Point {
+                                pos: *pos,
+                                entity: Entity::from_raw(i as u32),
+                            })
+                            .collect(),
+                    );
+                    for query in queries.iter() {
+                        black_box(tree.within_radius(&[query.x, query.y], QUERY_RADIUS));
+                    }
+                })
+            },
+        );
+
+        let mut index = EnemyIndex::default();
+        for (i, pos) in positions.iter().enumerate() {
+            index.update(Entity::from_raw(i as u32), *pos);
+        }
+        group.bench_with_input(BenchmarkId::new("enemy_index", count), &count, |b, _| {
+            b.iter(|| {
+                for (i, (pos, step)) in positions.iter_mut().zip(&steps).enumerate() {
+                    *pos += *step;
+                    index.update(Entity::from_raw(i as u32), *pos);
+                }
+                for query in queries.iter() {
+                    black_box(index.within_radius(*query, QUERY_RADIUS));
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_nearest(c: &mut Criterion) {
+    let mut group = c.benchmark_group("nearest_5");
+    for count in ENEMY_COUNTS {
+        let mut rng = StdRng::seed_from_u64(11);
+        let positions = random_positions(count, &mut rng);
+        let queries = random_positions(100, &mut rng);
+
+        let tree = KdTree::build_by_ordered_float(
+            positions
+                .iter()
+                .enumerate()
+                .map(|(i, pos)| Point {
+                    pos: *pos,
+                    entity: Entity::from_raw(i as u32),
+                })
+                .collect(),
+        );
+        group.bench_with_input(BenchmarkId::new("kd_tree", count), &count, |b, _| {
+            b.iter(|| {
+                for query in queries.iter() {
+                    black_box(
+                        tree.nearests(&[query.x, query.y], 5)
+                            .iter()
+                            .map(|found| found.item.entity)
+                            .collect::<Vec<_>>(),
+                    );
+                }
+            })
+        });
+
+        let mut index = EnemyIndex::default();
+        for (i, pos) in positions.iter().enumerate() {
+            index.update(Entity::from_raw(i as u32), *pos);
+        }
+        group.bench_with_input(BenchmarkId::new("enemy_index", count), &count, |b, _| {
+            b.iter(|| {
+                for query in queries.iter() {
+                    black_box(index.nearest(*query, 5));
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_tick, bench_nearest);
+criterion_main!(benches);
@@ -2,9 +2,10 @@ use bevy::prelude::*;
 
 use crate::{
     enemy::Enemy,
-    enemy::EnemyType,
+    enemy::{EnemyType, TeleportState},
     gun::Gun,
-    player::{Player, PlayerInventory, PlayerState},
+    inventory::PlayerInventory,
+    player::{Player, PlayerState},
     state::GameState,
     CursorPosition,
 };
@@ -22,11 +23,16 @@ impl Plugin for AnimationPlugin {
                 animation_timer_tick,
                 animate_player,
                 animate_enemy,
+                animate_teleporter_telegraph,
                 flip_gun_sprite_y,
                 flip_player_sprite_x,
                 flip_enemy_sprite_x,
             )
-                .run_if(in_state(GameState::InGame).or_else(in_state(GameState::Paused))),
+                .run_if(
+                    in_state(GameState::InGame)
+                        .or_else(in_state(GameState::Paused))
+                        .or_else(in_state(GameState::Town)),
+                ),
         );
     }
 }
@@ -68,6 +74,19 @@ fn animate_enemy(
     }
 }
 
+fn animate_teleporter_telegraph(time: Res<Time>, mut enemy_query: Query<(&mut Sprite, &Enemy)>) {
+    for (mut sprite, enemy) in enemy_query.iter_mut() {
+        if let EnemyType::Teleporter { state, .. } = &enemy.enemy_type {
+            let alpha = if *state == TeleportState::Telegraphing {
+                0.3 + 0.7 * (time.elapsed_seconds() * 20.0).sin().abs()
+            } else {
+                1.0
+            };
+            sprite.color.set_alpha(alpha);
+        }
+    }
+}
+
 fn flip_player_sprite_x(
     cursor_position: Res<CursorPosition>,
     mut player_query: Query<(&mut Sprite, &Transform), With<Player>>,
@@ -78,11 +97,7 @@ fn flip_player_sprite_x(
 
     let (mut sprite, transform) = player_query.single_mut();
     if let Some(cursor_position) = cursor_position.0 {
-        if cursor_position.x > transform.translation.x {
-            sprite.flip_x = false;
-        } else {
-            sprite.flip_x = true;
-        }
+        sprite.flip_x = cursor_position.x <= transform.translation.x;
     }
 }
 
@@ -96,11 +111,7 @@ fn flip_enemy_sprite_x(
 
     let player_pos = player_query.single().translation;
     for (mut sprite, transform) in enemy_query.iter_mut() {
-        if transform.translation.x < player_pos.x {
-            sprite.flip_x = false;
-        } else {
-            sprite.flip_x = true;
-        }
+        sprite.flip_x = transform.translation.x >= player_pos.x;
     }
 }
 
@@ -111,16 +122,12 @@ fn flip_gun_sprite_y(
 ) {
     // Check if player has an active gun
     if let Ok(inventory) = player_query.get_single() {
-        if let Some(active_gun) = inventory.guns.get(inventory.active_gun_index) {
+        if let Some(active_gun) = inventory.weapon {
             // Get the sprite and transform of the active gun
-            if let Ok((mut sprite, transform)) = gun_query.get_mut(*active_gun) {
+            if let Ok((mut sprite, transform)) = gun_query.get_mut(active_gun) {
                 if let Some(cursor_position) = cursor_position.0 {
                     // Flip the gun sprite based on cursor position relative to gun
-                    if cursor_position.x > transform.translation.x {
-                        sprite.flip_y = false;
-                    } else {
-                        sprite.flip_y = true;
-                    }
+                    sprite.flip_y = cursor_position.x <= transform.translation.x;
                 }
             }
         }
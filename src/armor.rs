@@ -1,45 +1,298 @@
 use crate::{
-    player::{Player, PlayerInventory},
+    dialog::{DialogAction, DialogActionEvent},
+    effects::{Effect, EffectAction, EffectSource, Stat},
+    gun::HasLifespan,
+    inventory::{
+        ArmorSlot, EquipmentSlot, InventoryAction, Item, ItemCategory, PlayerInventory, SlotId,
+    },
+    items::armor_sets,
+    player::{Player, PlayerDamagedEvent},
     state::GameState,
+    utils::{calculate_durability_loss, safe_subtract},
+    wallet::{CoinReason, Wallet, WalletAction},
     world::InGameEntity,
+    UiFont, ARMOR_LOW_DURABILITY, ARMOR_REPAIR_COST_PER_POINT,
 };
 use bevy::prelude::*;
+use bevy::utils::Duration;
+use rand::seq::SliceRandom;
 
 #[derive(Component)]
 pub struct Armor;
 
-#[derive(Component)]
+#[derive(Component, Clone)]
 pub struct ArmorStats {
+    pub slot: ArmorSlot,
     pub defense: u32,
+    /// Broken armor stays worn but adds nothing until it is repaired.
     pub durability: u32,
+    pub max_durability: u32,
+    /// Secondary stats on top of defense.
+    pub modifiers: Vec<(Stat, i32)>,
+    /// The name of the set in `armor_sets()` this piece belongs to.
+    pub set: Option<&'static str>,
+}
+
+/// Sent when a worn piece runs out of durability.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct ArmorBrokenEvent {
+    pub armor: Entity,
+    pub slot: ArmorSlot,
 }
 
 #[derive(Bundle)]
 pub struct ArmorBundle {
     pub armor: Armor,
+    pub item: Item,
     pub armor_stats: ArmorStats,
     pub in_game_entity: InGameEntity,
 }
 
 pub struct ArmorPlugin;
 
+impl ArmorStats {
+    pub fn new(slot: ArmorSlot, defense: u32, durability: u32) -> Self {
+        Self {
+            slot,
+            defense,
+            durability,
+            max_durability: durability,
+            modifiers: Vec::new(),
+            set: None,
+        }
+    }
+
+    pub fn with_modifier(mut self, stat: Stat, amount: i32) -> Self {
+        self.modifiers.push((stat, amount));
+        self
+    }
+
+    pub fn in_set(mut self, set: &'static str) -> Self {
+        self.set = Some(set);
+        self
+    }
+
+    pub fn is_broken(&self) -> bool {
+        self.durability == 0
+    }
+
+    pub fn is_worn_down(&self) -> bool {
+        (self.durability as f32) < self.max_durability as f32 * ARMOR_LOW_DURABILITY
+    }
+}
+
+/// What the blacksmith charges to restore `stats` to full durability.
+pub fn repair_cost(stats: &ArmorStats, item: &Item) -> u32 {
+    let missing = stats.max_durability.saturating_sub(stats.durability) as f32;
+    (missing * ARMOR_REPAIR_COST_PER_POINT * item.rarity.price_multiplier()).ceil() as u32
+}
+
+/// The cost of repairing all of `pieces`.
+pub fn total_repair_cost<'a>(pieces: impl IntoIterator<Item = (&'a ArmorStats, &'a Item)>) -> u32 {
+    pieces
+        .into_iter()
+        .map(|(stats, item)| repair_cost(stats, item))
+        .sum()
+}
+
 impl Plugin for ArmorPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, switch_armor.run_if(in_state(GameState::InGame)));
+        app.add_event::<ArmorBrokenEvent>()
+            .add_systems(
+                Update,
+                (switch_armor, wear_armor, announce_broken_armor)
+                    .run_if(in_state(GameState::InGame)),
+            )
+            .add_systems(Update, repair_armor.run_if(in_state(GameState::Town)))
+            .add_systems(Update, sync_armor_effect);
     }
 }
 
 fn switch_armor(
-    mut player_query: Query<&mut PlayerInventory, With<Player>>,
+    player_query: Query<&PlayerInventory, With<Player>>,
     keyboard_input: Res<ButtonInput<KeyCode>>,
+    item_query: Query<&Item>,
+    mut ev_inventory_action: EventWriter<InventoryAction>,
+    mut cursor: Local<usize>,
 ) {
     if player_query.is_empty() {
         return;
     }
 
-    let mut inventory = player_query.single_mut();
+    let inventory = player_query.single();
 
+    // Swap the next armor piece in the bag into the slot it is worn in
     if keyboard_input.just_pressed(KeyCode::KeyQ) {
-        inventory.active_armor_index = (inventory.active_armor_index + 1) % inventory.armors.len();
+        let slot_of = |entity: Entity| match item_query.get(entity).map(|item| item.category) {
+            Ok(ItemCategory::Armor(slot)) => Some(slot),
+            _ => None,
+        };
+        if let Some(index) = inventory.next_in_bag(*cursor, |entity| slot_of(entity).is_some()) {
+            *cursor = index;
+            if let Some(slot) = inventory.bag[index].and_then(slot_of) {
+                ev_inventory_action.send(InventoryAction::Swap {
+                    a: SlotId::Equipment(EquipmentSlot::Armor(slot)),
+                    b: SlotId::Bag(index),
+                });
+            }
+        }
+    }
+}
+
+/// Each hit wears down one random intact piece, by an amount that depends
+/// on the hit rather than on what got through.
+fn wear_armor(
+    player_query: Query<&PlayerInventory, With<Player>>,
+    mut armor_query: Query<&mut ArmorStats, With<Armor>>,
+    mut ev_damaged: EventReader<PlayerDamagedEvent>,
+    mut ev_broken: EventWriter<ArmorBrokenEvent>,
+) {
+    let Ok(inventory) = player_query.get_single() else {
+        ev_damaged.clear();
+        return;
+    };
+    let mut rng = rand::thread_rng();
+    for event in ev_damaged.read() {
+        let intact = inventory
+            .worn_armor()
+            .filter(|entity| {
+                armor_query
+                    .get(*entity)
+                    .is_ok_and(|stats| !stats.is_broken())
+            })
+            .collect::<Vec<_>>();
+        let Some(entity) = intact.choose(&mut rng) else {
+            continue;
+        };
+        let Ok(mut stats) = armor_query.get_mut(*entity) else {
+            continue;
+        };
+        stats.durability = safe_subtract(stats.durability, calculate_durability_loss(event.damage));
+        if stats.is_broken() {
+            ev_broken.send(ArmorBrokenEvent {
+                armor: *entity,
+                slot: stats.slot,
+            });
+        }
+    }
+}
+
+fn announce_broken_armor(
+    mut commands: Commands,
+    player_query: Query<&Transform, With<Player>>,
+    mut ev_broken: EventReader<ArmorBrokenEvent>,
+    font: Res<UiFont>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {
+        ev_broken.clear();
+        return;
+    };
+    for event in ev_broken.read() {
+        commands.spawn((
+            Text2dBundle {
+                text: Text::from_section(
+                    format!("{} armor broke!", event.slot.name()),
+                    TextStyle {
+                        font: font.0.clone(),
+                        font_size: 40.0,
+                        color: Color::srgb(1.0, 0.6, 0.1),
+                    },
+                ),
+                transform: Transform::from_translation(
+                    player_transform.translation + Vec3::new(0.0, 90.0, 30.0),
+                ),
+                ..default()
+            },
+            HasLifespan::new(Duration::from_secs(2)),
+            InGameEntity,
+        ));
+    }
+}
+
+/// Restores every armor piece the player carries when the blacksmith is
+/// asked to, if they can pay for all of it.
+fn repair_armor(
+    mut ev_action: EventReader<DialogActionEvent>,
+    player_query: Query<&PlayerInventory, With<Player>>,
+    mut armor_query: Query<(&mut ArmorStats, &Item)>,
+    wallet: Res<Wallet>,
+    mut ev_wallet: EventWriter<WalletAction>,
+) {
+    let Ok(inventory) = player_query.get_single() else {
+        ev_action.clear();
+        return;
+    };
+    for event in ev_action.read() {
+        if !matches!(event.action, DialogAction::RepairArmor) {
+            continue;
+        }
+        let cost = total_repair_cost(
+            inventory
+                .slots()
+                .filter_map(|(_, entity)| armor_query.get(entity).ok()),
+        );
+        if cost == 0 || wallet.coins < cost {
+            continue;
+        }
+        ev_wallet.send(WalletAction::Spend {
+            amount: cost,
+            reason: CoinReason::Repair,
+        });
+        for (_, entity) in inventory.slots() {
+            if let Ok((mut stats, _)) = armor_query.get_mut(entity) {
+                stats.durability = stats.max_durability;
+            }
+        }
+    }
+}
+
+/// Worn armor adds its defense, secondary stats and any set bonuses through
+/// one lasting effect, rebuilt whenever the inventory or a piece changes.
+/// Broken pieces count for nothing.
+fn sync_armor_effect(
+    player_query: Query<(Entity, Ref<PlayerInventory>), With<Player>>,
+    armor_query: Query<Ref<ArmorStats>, With<Armor>>,
+    mut ev_effect: EventWriter<EffectAction>,
+) {
+    let Ok((player, inventory)) = player_query.get_single() else {
+        return;
+    };
+    let worn = inventory
+        .worn_armor()
+        .filter_map(|entity| armor_query.get(entity).ok())
+        .collect::<Vec<_>>();
+    if !inventory.is_changed() && !worn.iter().any(|stats| stats.is_changed()) {
+        return;
+    }
+    let worn = worn
+        .iter()
+        .filter(|stats| !stats.is_broken())
+        .map(|stats| stats.as_ref())
+        .collect::<Vec<_>>();
+    if worn.is_empty() {
+        ev_effect.send(EffectAction::Remove {
+            target: player,
+            source: EffectSource::Armor,
+        });
+        return;
+    }
+
+    let defense = worn.iter().map(|stats| stats.defense as i32).sum();
+    let mut effect = Effect::new(EffectSource::Armor, None).with_modifier(Stat::Defense, defense);
+    for (stat, amount) in worn.iter().flat_map(|stats| stats.modifiers.iter()) {
+        effect = effect.with_modifier(*stat, *amount);
+    }
+    for set in armor_sets() {
+        let pieces = worn
+            .iter()
+            .filter(|stats| stats.set == Some(set.name))
+            .count();
+        for (stat, amount) in set.active_bonuses(pieces) {
+            effect = effect.with_modifier(*stat, *amount);
+        }
     }
+    ev_effect.send(EffectAction::Apply {
+        target: player,
+        effect,
+    });
 }
@@ -8,12 +8,15 @@ pub struct FollowCameraPlugin;
 
 impl Plugin for FollowCameraPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(PanCamPlugin::default())
+        app.add_plugins(PanCamPlugin)
             .add_systems(OnEnter(GameState::Loading), setup_camera)
             .add_systems(
                 Update,
-                camera_follow_player
-                    .run_if(in_state(GameState::InGame).or_else(in_state(GameState::Paused))),
+                camera_follow_player.run_if(
+                    in_state(GameState::InGame)
+                        .or_else(in_state(GameState::Paused))
+                        .or_else(in_state(GameState::Town)),
+                ),
             );
     }
 }
@@ -1,155 +1,669 @@
 use bevy::time::Stopwatch;
 use bevy::utils::Duration;
 
-use bevy::{prelude::*, time::common_conditions::on_timer};
-use kd_tree::{KdPoint, KdTree};
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::tasks::{ComputeTaskPool, ParallelSlice};
+use bevy::utils::HashSet;
 use player::InvincibilityEffect;
+use rand::Rng;
 use utils::safe_subtract;
 
+use crate::effects::{
+    ActiveEffects, Effect, EffectAction, EffectSource, StackingRule, Stat, TickEffect,
+};
+use crate::enemy::{Elite, EliteAffix, EnemyDamagedEvent};
+use crate::gun::{BulletDirection, BulletStats, HasLifespan, OnHitEffect};
+use crate::knockback::KnockbackEvent;
 use crate::player::{Player, PlayerDamagedEvent};
+use crate::spatial_hash::SpatialHash;
+use crate::spatial_index::EnemyIndex;
+use crate::world::{InGameEntity, Obstacle};
 use crate::*;
 use crate::{enemy::Enemy, enemy::Trail, gun::Bullet, state::GameState};
 
 pub struct CollisionPlugin;
 
-#[derive(Component)]
-struct Collidable {
-    pos: Vec2,
-    entity: Entity,
-    damage: u32,
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CollisionLayer {
+    Player,
+    Enemy,
+    PlayerBullet,
+    EnemyBullet,
+    Hazard,
+    Pickup,
+    Interactable,
+    Obstacle,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct CollisionMask(pub u32);
+
+#[derive(Clone, Copy, Debug)]
+pub enum ColliderShape {
+    Circle { radius: f32 },
+    Aabb { half_extents: Vec2 },
+}
+
+#[derive(Component, Clone, Copy)]
+pub struct Collider {
+    pub shape: ColliderShape,
+    pub layer: CollisionLayer,
+    pub mask: CollisionMask,
+}
+
+/// A player bullet touching an enemy.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct BulletHitEnemy {
+    pub bullet: Entity,
+    pub enemy: Entity,
+}
+
+/// An enemy bullet touching the player.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct BulletHitPlayer {
+    pub bullet: Entity,
+    pub player: Entity,
 }
 
-#[derive(Resource)]
-struct EnemyKdTree(KdTree<Collidable>);
+/// An enemy touching the player.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct EnemyTouchedPlayer {
+    pub enemy: Entity,
+    pub player: Entity,
+}
+
+/// The player standing in a hazard, such as a trail.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct PlayerInHazard {
+    pub hazard: Entity,
+    pub player: Entity,
+}
+
+/// The player touching an item or coin on the ground.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct PlayerTouchedPickup {
+    pub pickup: Entity,
+    pub player: Entity,
+}
+
+/// The player close enough to use an interactable, such as a portal.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct PlayerNearInteractable {
+    pub interactable: Entity,
+    pub player: Entity,
+}
+
+/// Anything touching an obstacle. `layer` is the other entity's layer.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct HitObstacle {
+    pub entity: Entity,
+    pub layer: CollisionLayer,
+    pub obstacle: Entity,
+}
+
+/// One writer per typed collision event, filled by `detect_collisions`.
+#[derive(SystemParam)]
+pub struct CollisionWriters<'w> {
+    bullet_hit_enemy: EventWriter<'w, BulletHitEnemy>,
+    bullet_hit_player: EventWriter<'w, BulletHitPlayer>,
+    enemy_touched_player: EventWriter<'w, EnemyTouchedPlayer>,
+    player_in_hazard: EventWriter<'w, PlayerInHazard>,
+    player_touched_pickup: EventWriter<'w, PlayerTouchedPickup>,
+    player_near_interactable: EventWriter<'w, PlayerNearInteractable>,
+    hit_obstacle: EventWriter<'w, HitObstacle>,
+}
+
+/// Keeps `EnemyIndex` in sync with enemy positions. Systems that read the
+/// index run after this set.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct EnemyIndexSet;
 
 impl Plugin for CollisionPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(EnemyKdTree::default()).add_systems(
-            Update,
-            (
-                handle_enemy_bullet_collision,
-                handle_enemy_player_collision,
-                handle_player_trail_collision,
-                update_enemy_kd_tree
-                    .run_if(on_timer(Duration::from_secs_f32(KD_TREE_REFRESH_RATE))),
-            )
-                .run_if(in_state(GameState::InGame)),
-        );
+        app.init_resource::<EnemyIndex>()
+            .add_event::<BulletHitEnemy>()
+            .add_event::<BulletHitPlayer>()
+            .add_event::<EnemyTouchedPlayer>()
+            .add_event::<PlayerInHazard>()
+            .add_event::<PlayerTouchedPickup>()
+            .add_event::<PlayerNearInteractable>()
+            .add_event::<HitObstacle>()
+            .add_systems(OnEnter(GameState::GameInit), reset_enemy_index)
+            .add_systems(
+                Update,
+                (
+                    update_enemy_index
+                        .in_set(EnemyIndexSet)
+                        .before(detect_collisions),
+                    detect_collisions,
+                    (
+                        handle_enemy_bullet_collision,
+                        handle_enemy_player_collision,
+                        handle_player_trail_collision,
+                        handle_bullet_obstacle_collision,
+                        resolve_obstacle_penetration,
+                    )
+                        .after(detect_collisions),
+                )
+                    .run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+impl CollisionLayer {
+    pub const fn bit(self) -> u32 {
+        1 << self as u32
+    }
+
+    /// Enemies don't report collisions with each other, their separation is
+    /// handled by `handle_enemy_collision`.
+    pub fn default_mask(self) -> CollisionMask {
+        use CollisionLayer::*;
+        match self {
+            Player => {
+                CollisionMask::of(&[Enemy, EnemyBullet, Hazard, Pickup, Interactable, Obstacle])
+            }
+            Enemy => CollisionMask::of(&[Player, PlayerBullet, Obstacle]),
+            PlayerBullet => CollisionMask::of(&[Enemy, Obstacle]),
+            EnemyBullet => CollisionMask::of(&[Player, Obstacle]),
+            Hazard | Pickup | Interactable => CollisionMask::of(&[Player]),
+            Obstacle => CollisionMask::of(&[Player, Enemy, PlayerBullet, EnemyBullet]),
+        }
+    }
+}
+
+impl CollisionMask {
+    pub fn of(layers: &[CollisionLayer]) -> Self {
+        Self(layers.iter().fold(0, |mask, layer| mask | layer.bit()))
+    }
+
+    pub fn contains(&self, layer: CollisionLayer) -> bool {
+        self.0 & layer.bit() != 0
+    }
+}
+
+impl Collider {
+    pub fn circle(radius: f32, layer: CollisionLayer) -> Self {
+        Self {
+            shape: ColliderShape::Circle { radius },
+            layer,
+            mask: layer.default_mask(),
+        }
+    }
+
+    pub fn aabb(half_extents: Vec2, layer: CollisionLayer) -> Self {
+        Self {
+            shape: ColliderShape::Aabb { half_extents },
+            layer,
+            mask: layer.default_mask(),
+        }
+    }
+
+    pub fn with_mask(mut self, mask: CollisionMask) -> Self {
+        self.mask = mask;
+        self
+    }
+
+    /// Both colliders have to list the other's layer in their mask.
+    pub fn interacts_with(&self, other: &Collider) -> bool {
+        self.mask.contains(other.layer) && other.mask.contains(self.layer)
+    }
+
+    pub fn half_extents(&self) -> Vec2 {
+        match self.shape {
+            ColliderShape::Circle { radius } => Vec2::splat(radius),
+            ColliderShape::Aabb { half_extents } => half_extents,
+        }
+    }
+}
+
+impl CollisionWriters<'_> {
+    /// Sends the typed event for a pair of overlapping colliders. Pairs that
+    /// no gameplay system cares about are dropped.
+    fn send(&mut self, a: (Entity, CollisionLayer), b: (Entity, CollisionLayer)) {
+        use CollisionLayer::*;
+        // Order by layer so each pair only has to be matched one way round
+        let ((a, a_layer), (b, b_layer)) = if a.1 as u32 <= b.1 as u32 {
+            (a, b)
+        } else {
+            (b, a)
+        };
+        match (a_layer, b_layer) {
+            (Player, Enemy) => {
+                self.enemy_touched_player.send(EnemyTouchedPlayer {
+                    enemy: b,
+                    player: a,
+                });
+            }
+            (Player, EnemyBullet) => {
+                self.bullet_hit_player.send(BulletHitPlayer {
+                    bullet: b,
+                    player: a,
+                });
+            }
+            (Player, Hazard) => {
+                self.player_in_hazard.send(PlayerInHazard {
+                    hazard: b,
+                    player: a,
+                });
+            }
+            (Player, Pickup) => {
+                self.player_touched_pickup.send(PlayerTouchedPickup {
+                    pickup: b,
+                    player: a,
+                });
+            }
+            (Player, Interactable) => {
+                self.player_near_interactable.send(PlayerNearInteractable {
+                    interactable: b,
+                    player: a,
+                });
+            }
+            (Enemy, PlayerBullet) => {
+                self.bullet_hit_enemy.send(BulletHitEnemy {
+                    bullet: b,
+                    enemy: a,
+                });
+            }
+            (_, Obstacle) => {
+                self.hit_obstacle.send(HitObstacle {
+                    entity: a,
+                    layer: a_layer,
+                    obstacle: b,
+                });
+            }
+            _ => {}
+        }
+    }
+}
+
+pub fn shapes_overlap(a: ColliderShape, a_pos: Vec2, b: ColliderShape, b_pos: Vec2) -> bool {
+    match (a, b) {
+        (ColliderShape::Circle { radius: ra }, ColliderShape::Circle { radius: rb }) => {
+            a_pos.distance_squared(b_pos) <= (ra + rb) * (ra + rb)
+        }
+        (ColliderShape::Circle { radius }, ColliderShape::Aabb { half_extents }) => {
+            circle_aabb_overlap(a_pos, radius, b_pos, half_extents)
+        }
+        (ColliderShape::Aabb { half_extents }, ColliderShape::Circle { radius }) => {
+            circle_aabb_overlap(b_pos, radius, a_pos, half_extents)
+        }
+        (ColliderShape::Aabb { half_extents: ha }, ColliderShape::Aabb { half_extents: hb }) => {
+            let delta = (a_pos - b_pos).abs();
+            delta.x <= ha.x + hb.x && delta.y <= ha.y + hb.y
+        }
+    }
+}
+
+fn circle_aabb_overlap(center: Vec2, radius: f32, box_pos: Vec2, half_extents: Vec2) -> bool {
+    let closest = center.clamp(box_pos - half_extents, box_pos + half_extents);
+    center.distance_squared(closest) <= radius * radius
+}
+
+/// How far a shape at `pos` has to move to stop overlapping the box.
+pub fn aabb_penetration(
+    shape: ColliderShape,
+    pos: Vec2,
+    box_pos: Vec2,
+    half_extents: Vec2,
+) -> Vec2 {
+    let extents = match shape {
+        ColliderShape::Circle { radius } => {
+            let closest = pos.clamp(box_pos - half_extents, box_pos + half_extents);
+            let offset = pos - closest;
+            if offset != Vec2::ZERO {
+                let distance = offset.length();
+                return offset / distance * (radius - distance).max(0.0);
+            }
+            Vec2::splat(radius)
+        }
+        ColliderShape::Aabb { half_extents } => half_extents,
+    };
+
+    // The center is inside the box, push out along the shallowest axis
+    let delta = pos - box_pos;
+    let overlap = half_extents + extents - delta.abs();
+    if overlap.x <= 0.0 || overlap.y <= 0.0 {
+        Vec2::ZERO
+    } else if overlap.x < overlap.y {
+        Vec2::new(overlap.x * delta.x.signum(), 0.0)
+    } else {
+        Vec2::new(0.0, overlap.y * delta.y.signum())
     }
 }
 
+/// Slab test for the segment `start..end` against a box.
+pub fn segment_intersects_aabb(start: Vec2, end: Vec2, box_pos: Vec2, half_extents: Vec2) -> bool {
+    let (min, max) = (box_pos - half_extents, box_pos + half_extents);
+    let direction = end - start;
+    let (mut t_min, mut t_max) = (0.0_f32, 1.0_f32);
+    for axis in 0..2 {
+        if direction[axis].abs() < f32::EPSILON {
+            if start[axis] < min[axis] || start[axis] > max[axis] {
+                return false;
+            }
+            continue;
+        }
+        let inverse = 1.0 / direction[axis];
+        let t1 = (min[axis] - start[axis]) * inverse;
+        let t2 = (max[axis] - start[axis]) * inverse;
+        t_min = t_min.max(t1.min(t2));
+        t_max = t_max.min(t1.max(t2));
+        if t_min > t_max {
+            return false;
+        }
+    }
+    true
+}
+
+/// True if no obstacle box lies between the two points.
+pub fn has_line_of_sight<'a>(
+    from: Vec2,
+    to: Vec2,
+    obstacles: impl IntoIterator<Item = (&'a Transform, &'a Collider)>,
+) -> bool {
+    obstacles.into_iter().all(|(transform, collider)| {
+        !segment_intersects_aabb(
+            from,
+            to,
+            transform.translation.truncate(),
+            collider.half_extents(),
+        )
+    })
+}
+
+/// Broadphase over every collider. Sends one typed collision event per
+/// overlapping pair whose masks accept each other.
+pub fn detect_collisions(
+    collider_query: Query<(Entity, &Transform, &Collider)>,
+    mut writers: CollisionWriters,
+) {
+    let bodies = collider_query
+        .iter()
+        .map(|(entity, transform, collider)| (entity, transform.translation.truncate(), *collider))
+        .collect::<Vec<_>>();
+    if bodies.len() < 2 {
+        return;
+    }
+
+    let mut grid = SpatialHash::new(COLLISION_CELL_SIZE);
+    for (index, (_, pos, collider)) in bodies.iter().enumerate() {
+        let half_extents = collider.half_extents();
+        grid.insert_aabb(index, *pos - half_extents, *pos + half_extents);
+    }
+
+    let indices = (0..bodies.len()).collect::<Vec<_>>();
+    let events = indices.par_chunk_map(ComputeTaskPool::get(), 256, |_, chunk| {
+        let mut events = Vec::new();
+        let mut candidates = Vec::new();
+        for &index in chunk {
+            let (entity, pos, collider) = bodies[index];
+            let half_extents = collider.half_extents();
+
+            // Only look at higher indices so each pair is tested once
+            candidates.clear();
+            candidates.extend(
+                grid.query_aabb(pos - half_extents, pos + half_extents)
+                    .filter(|&other| other > index),
+            );
+            candidates.sort_unstable();
+            candidates.dedup();
+
+            for &other in candidates.iter() {
+                let (other_entity, other_pos, other_collider) = bodies[other];
+                if collider.interacts_with(&other_collider)
+                    && shapes_overlap(collider.shape, pos, other_collider.shape, other_pos)
+                {
+                    events.push((
+                        (entity, collider.layer),
+                        (other_entity, other_collider.layer),
+                    ));
+                }
+            }
+        }
+        events
+    });
+
+    for (a, b) in events.into_iter().flatten() {
+        writers.send(a, b);
+    }
+}
+
+#[allow(clippy::type_complexity)]
 fn handle_enemy_player_collision(
     mut commands: Commands,
-    mut player_query: Query<(&Transform, Entity), (With<Player>, Without<InvincibilityEffect>)>,
-    tree: Res<EnemyKdTree>,
+    player_query: Query<
+        (&Transform, Entity, Option<&ActiveEffects>),
+        (With<Player>, Without<InvincibilityEffect>),
+    >,
+    mut enemy_query: Query<(&mut Enemy, &Transform, Option<&Elite>), Without<Player>>,
+    mut ev_contact: EventReader<EnemyTouchedPlayer>,
     mut ew: EventWriter<PlayerDamagedEvent>,
+    mut ev_knockback: EventWriter<KnockbackEvent>,
 ) {
-    if player_query.is_empty() {
+    let Ok((player_transform, player_entity, effects)) = player_query.get_single() else {
+        ev_contact.clear();
         return;
-    }
+    };
+    let player_pos = player_transform.translation.truncate();
+    let thorns = effects.map_or(0, |effects| effects.modifier(Stat::Thorns).max(0) as u32);
 
-    let (translation, entity) = player_query.single_mut();
+    for contact in ev_contact.read() {
+        let enemy_entity = contact.enemy;
+        let Ok((mut enemy, enemy_transform, elite)) = enemy_query.get_mut(enemy_entity) else {
+            continue;
+        };
+        if enemy.damage == 0 {
+            continue;
+        }
 
-    let player_pos = translation.translation;
+        commands.entity(player_entity).insert(InvincibilityEffect(
+            Stopwatch::new(),
+            PLAYER_INVINCIBLE_TIME,
+        ));
+        ew.send(PlayerDamagedEvent {
+            damage: enemy.damage,
+        });
+        enemy.health = safe_subtract(enemy.health, enemy.damage * thorns / 100);
 
-    let enemies = tree.0.within_radius(&[player_pos.x, player_pos.y], 50.0);
-    if let Some(enemy) = enemies.first() {
-        if enemy.damage > 0 {
-            commands.entity(entity).insert(InvincibilityEffect(
-                Stopwatch::new(),
-                PLAYER_INVINCIBLE_TIME,
-            ));
-            ew.send(PlayerDamagedEvent {
-                damage: enemy.damage,
-            });
+        let push = (player_pos - enemy_transform.translation.truncate()).normalize_or_zero();
+        ev_knockback.send(KnockbackEvent {
+            target: player_entity,
+            impulse: push * CONTACT_KNOCKBACK,
+            stun: CONTACT_HIT_STUN,
+        });
+        ev_knockback.send(KnockbackEvent {
+            target: enemy_entity,
+            impulse: -push * CONTACT_KNOCKBACK * 0.5,
+            stun: CONTACT_HIT_STUN,
+        });
+        if elite.is_some_and(|elite| elite.has(EliteAffix::Vampiric)) && enemy.health > 0 {
+            enemy.health = (enemy.health + enemy.damage).min(enemy.max_health);
         }
+        break;
     }
+    ev_contact.clear();
 }
 
 fn handle_player_trail_collision(
     mut commands: Commands,
-    mut player_query: Query<(&Transform, Entity), (With<Player>, Without<InvincibilityEffect>)>,
-    trail_query: Query<(&Transform, &Trail)>,
+    player_query: Query<Entity, (With<Player>, Without<InvincibilityEffect>)>,
+    trail_query: Query<&Trail>,
+    mut ev_hazard: EventReader<PlayerInHazard>,
     mut ew: EventWriter<PlayerDamagedEvent>,
+    mut ev_effect: EventWriter<EffectAction>,
 ) {
-    if player_query.is_empty() {
+    let Ok(player_entity) = player_query.get_single() else {
+        ev_hazard.clear();
         return;
-    }
+    };
 
-    let (translation, entity) = player_query.single_mut();
-    let player_pos = translation.translation.xy();
-    for (trail_transform, trail) in trail_query.iter() {
-        let trail_pos = trail_transform.translation.xy();
-        if player_pos.distance(trail_pos) <= trail.radius as f32 {
-            commands.entity(entity).insert(InvincibilityEffect(
+    for hazard in ev_hazard.read() {
+        if let Ok(trail) = trail_query.get(hazard.hazard) {
+            commands.entity(player_entity).insert(InvincibilityEffect(
                 Stopwatch::new(),
                 PLAYER_INVINCIBLE_TIME,
             ));
             ew.send(PlayerDamagedEvent {
                 damage: trail.damage,
             });
+            // The trail sticks, slowing and poisoning for a while after
+            ev_effect.send(EffectAction::Apply {
+                target: player_entity,
+                effect: Effect::new(EffectSource::Trail, Some(TRAIL_DEBUFF_DURATION))
+                    .with_modifier(Stat::Speed, -TRAIL_DEBUFF_SLOW)
+                    .with_tick(TickEffect::Damage(TRAIL_DEBUFF_POISON))
+                    .with_stacking(StackingRule::Stack(TRAIL_DEBUFF_MAX_STACKS)),
+            });
             break;
         }
     }
+    ev_hazard.clear();
 }
 
-fn update_enemy_kd_tree(
-    mut tree: ResMut<EnemyKdTree>,
-    enemy_query: Query<(&Transform, Entity, &Enemy), With<Enemy>>,
+fn handle_bullet_obstacle_collision(
+    mut commands: Commands,
+    bullet_query: Query<&Transform, With<BulletStats>>,
+    mut ev_obstacle: EventReader<HitObstacle>,
 ) {
-    let mut items = Vec::new();
-    for (t, e, enemy) in enemy_query.iter() {
-        items.push(Collidable {
-            entity: e,
-            pos: t.translation.truncate(),
-            damage: enemy.damage,
-        })
+    for hit in ev_obstacle.read() {
+        if !matches!(
+            hit.layer,
+            CollisionLayer::PlayerBullet | CollisionLayer::EnemyBullet
+        ) {
+            continue;
+        }
+        let bullet_entity = hit.entity;
+        let Ok(transform) = bullet_query.get(bullet_entity) else {
+            continue;
+        };
+
+        spawn_impact(&mut commands, transform.translation);
+        commands.add(move |world: &mut World| {
+            if let Some(entity) = world.get_entity_mut(bullet_entity) {
+                entity.despawn();
+            }
+        });
     }
+}
 
-    tree.0 = KdTree::build_by_ordered_float(items);
+fn spawn_impact(commands: &mut Commands, position: Vec3) {
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::srgba(1.0, 0.9, 0.6, 0.8),
+                custom_size: Some(Vec2::splat(12.0)),
+                ..default()
+            },
+            transform: Transform::from_translation(position.truncate().extend(2.0)),
+            ..default()
+        },
+        HasLifespan::new(Duration::from_secs_f32(0.15)),
+        InGameEntity,
+    ));
 }
 
-fn handle_enemy_bullet_collision(
-    mut commands: Commands,
-    bullet_query: Query<(&Transform, Entity), With<Bullet>>,
-    tree: Res<EnemyKdTree>,
-    mut enemy_query: Query<(&Transform, &mut Enemy), With<Enemy>>,
+/// Pushes the player and enemies back out of any obstacle they walked into.
+fn resolve_obstacle_penetration(
+    mut mover_query: Query<(&mut Transform, &Collider), Without<Obstacle>>,
+    obstacle_query: Query<(&Transform, &Collider), With<Obstacle>>,
+    mut ev_obstacle: EventReader<HitObstacle>,
 ) {
-    if bullet_query.is_empty() || enemy_query.is_empty() {
-        return;
-    }
-
-    for (bullet_transform, bullet_entity) in bullet_query.iter() {
-        let pos = bullet_transform.translation;
-        let enemies_in_radius = tree.0.within_radius(&[pos.x, pos.y], 30.0);
-
-        if let Some(enemy) = enemies_in_radius.first() {
-            if let Ok((_, mut enemy)) = enemy_query.get_mut(enemy.entity) {
-                enemy.health = safe_subtract(enemy.health, BULLET_DAMAGE);
-                commands.add(move |world: &mut World| {
-                    if let Some(entity) = world.get_entity_mut(bullet_entity) {
-                        entity.despawn();
-                    }
-                });
-            }
+    for hit in ev_obstacle.read() {
+        if !matches!(hit.layer, CollisionLayer::Player | CollisionLayer::Enemy) {
+            continue;
         }
+        let (Ok((mut transform, collider)), Ok((obstacle_transform, obstacle_collider))) = (
+            mover_query.get_mut(hit.entity),
+            obstacle_query.get(hit.obstacle),
+        ) else {
+            continue;
+        };
+
+        let push = aabb_penetration(
+            collider.shape,
+            transform.translation.truncate(),
+            obstacle_transform.translation.truncate(),
+            obstacle_collider.half_extents(),
+        );
+        transform.translation += push.extend(0.0);
     }
 }
 
-impl KdPoint for Collidable {
-    type Scalar = f32;
-    type Dim = typenum::U2;
-    fn at(&self, k: usize) -> f32 {
-        if k == 0 {
-            return self.pos.x;
-        }
+fn reset_enemy_index(mut index: ResMut<EnemyIndex>) {
+    index.clear();
+}
 
-        self.pos.y
+#[allow(clippy::type_complexity)]
+fn update_enemy_index(
+    mut index: ResMut<EnemyIndex>,
+    enemy_query: Query<(Entity, &Transform), (With<Enemy>, Changed<Transform>)>,
+    mut removed: RemovedComponents<Enemy>,
+) {
+    for entity in removed.read() {
+        index.remove(entity);
+    }
+    for (entity, transform) in enemy_query.iter() {
+        index.update(entity, transform.translation.truncate());
     }
 }
 
-impl Default for EnemyKdTree {
-    fn default() -> Self {
-        Self(KdTree::build_by_ordered_float(vec![]))
+fn handle_enemy_bullet_collision(
+    mut commands: Commands,
+    bullet_query: Query<(&BulletStats, &BulletDirection, Option<&OnHitEffect>), With<Bullet>>,
+    mut ev_hit: EventReader<BulletHitEnemy>,
+    mut ev_effect: EventWriter<EffectAction>,
+    mut ev_enemy_damaged: EventWriter<EnemyDamagedEvent>,
+    mut ev_knockback: EventWriter<KnockbackEvent>,
+    mut enemy_query: Query<(&mut Enemy, &Transform, Option<&mut Elite>), With<Enemy>>,
+) {
+    let mut rng = rand::thread_rng();
+    let mut spent_bullets = HashSet::new();
+    for hit in ev_hit.read() {
+        let (bullet_entity, enemy_entity) = (hit.bullet, hit.enemy);
+        // A bullet only hits the first enemy it overlaps
+        if spent_bullets.contains(&bullet_entity) {
+            continue;
+        }
+        let Ok((bullet_stats, direction, on_hit)) = bullet_query.get(bullet_entity) else {
+            continue;
+        };
+        let Ok((mut enemy, enemy_transform, elite)) = enemy_query.get_mut(enemy_entity) else {
+            continue;
+        };
+        spent_bullets.insert(bullet_entity);
+
+        if !elite.is_some_and(|mut elite| elite.absorb_hit()) {
+            let crit = rng.gen::<f32>() < BULLET_CRIT_CHANCE;
+            let damage = if crit {
+                bullet_stats.damage * BULLET_CRIT_MULTIPLIER
+            } else {
+                bullet_stats.damage
+            };
+            enemy.health = safe_subtract(enemy.health, damage);
+            ev_enemy_damaged.send(EnemyDamagedEvent {
+                entity: enemy_entity,
+                position: enemy_transform.translation,
+                damage,
+                crit,
+            });
+            ev_knockback.send(KnockbackEvent {
+                target: enemy_entity,
+                impulse: direction.0.truncate().normalize_or_zero() * BULLET_KNOCKBACK,
+                stun: BULLET_HIT_STUN,
+            });
+            if let Some(on_hit) = on_hit {
+                ev_effect.send(EffectAction::Apply {
+                    target: enemy_entity,
+                    effect: on_hit.0.into(),
+                });
+            }
+        }
+        commands.add(move |world: &mut World| {
+            if let Some(entity) = world.get_entity_mut(bullet_entity) {
+                entity.despawn();
+            }
+        });
     }
 }
@@ -15,11 +15,16 @@ pub const SPRITE_SHEET_H: u32 = 8;
 pub const NUM_WORLD_DECORATIONS: usize = 500;
 pub const WORLD_W: f32 = 4000.0;
 pub const WORLD_H: f32 = 2000.0;
+pub const NUM_OBSTACLES: usize = 40;
+pub const OBSTACLE_MIN_SIZE: f32 = 40.0;
+pub const OBSTACLE_MAX_SIZE: f32 = 200.0;
+pub const OBSTACLE_SPAWN_CLEARANCE: f32 = 300.0;
 
 // Player
 pub const PLAYER_SPEED: u32 = 15;
 pub const PLAYER_HEALTH: u32 = 100;
 pub const PLAYER_INVINCIBLE_TIME: f32 = 0.2;
+pub const PLAYER_MASS: f32 = 1.0;
 
 // Enemy
 pub const MAX_NUM_ENEMIES: usize = 2000;
@@ -28,9 +33,135 @@ pub const ENEMY_HEALTH: u32 = 100;
 pub const SPAWN_RATE_PER_SECOND: usize = 1000;
 pub const ENEMY_SPAWN_INTERVAL: f32 = 1.0;
 pub const ENEMY_SPEED: u32 = 6;
+pub const ENEMY_BULLET_SPEED: u32 = 200;
+pub const ENEMY_BULLET_DAMAGE: u32 = 10;
+pub const ENEMY_BULLET_TIME_SECS: f32 = 2.0;
+pub const SPLITTER_MAX_GENERATION: u32 = 2;
+pub const SPLITTER_SPLIT_COUNT: u32 = 2;
 
-// Kd-tree
-pub const KD_TREE_REFRESH_RATE: f32 = 0.1;
+// Elite
+pub const ELITE_BASE_CHANCE: f32 = 0.02;
+pub const ELITE_CHANCE_PER_WAVE: f32 = 0.01;
+pub const ELITE_MAX_CHANCE: f32 = 0.3;
+pub const ELITE_HEALTH_MULTIPLIER: u32 = 3;
+pub const ELITE_XP_MULTIPLIER: u32 = 4;
+pub const ELITE_SCALE_MULTIPLIER: f32 = 1.4;
+pub const ELITE_MASS_MULTIPLIER: f32 = 2.0;
+pub const ELITE_SHIELD_HITS: u32 = 5;
+pub const ELITE_REGEN_INTERVAL: f32 = 1.0;
+pub const ELITE_REGEN_AMOUNT: u32 = 5;
+pub const ELITE_EXPLOSION_RADIUS: f32 = 120.0;
+pub const ELITE_EXPLOSION_DAMAGE: u32 = 20;
+
+// Loot
+pub const LOOT_CHANCE_PER_WAVE: f32 = 0.05;
+pub const ELITE_LOOT_MULTIPLIER: f32 = 2.0;
+pub const LOOT_MAX_CHANCE: f32 = 0.95;
+pub const LOOT_Z: f32 = 0.8;
+
+// Pickups
+pub const PICKUP_RADIUS: f32 = 20.0;
+pub const PICKUP_MAGNET_RADIUS: f32 = 150.0;
+pub const PICKUP_MAGNET_SPEED: f32 = 400.0;
+pub const PICKUP_FULL_MESSAGE_COOLDOWN: f32 = 1.0;
+// Far enough from the player that a dropped item sits outside the pickup radius
+pub const PICKUP_DROP_DISTANCE: f32 = 60.0;
+
+// Inventory
+pub const INVENTORY_BAG_SIZE: usize = 20;
+pub const QUICK_USE_SLOTS: usize = 4;
+pub const QUICK_USE_COOLDOWN: f32 = 1.0;
+pub const HOTBAR_SLOT_SIZE: f32 = 56.0;
+pub const MAX_CONSUMABLE_STACK: u32 = 10;
+pub const MAX_MATERIAL_STACK: u32 = 50;
+pub const INVENTORY_SLOT_SIZE: f32 = 64.0;
+
+// Dialog
+pub const DIALOG_SCRIPT_DIR: &str = "embedded://fishmans_adventure/../assets/dialogs";
+pub const DIALOG_BOX_HEIGHT: f32 = 260.0;
+pub const DIALOG_CHARS_PER_SECOND: f32 = 40.0;
+pub const DIALOG_CHOICE_STEP: f32 = 44.0;
+pub const DIALOG_VISIBLE_CHOICES: usize = 3;
+
+// Town
+pub const TOWN_W: f32 = 1200.0;
+pub const TOWN_H: f32 = 700.0;
+pub const TOWN_SPAWN_Y: f32 = -100.0;
+pub const TOWN_EXIT_Y: f32 = -280.0;
+pub const TOWN_INTERACT_RADIUS: f32 = 80.0;
+
+// Shop
+pub const SHOP_STOCK_SIZE: usize = 6;
+pub const SHOP_POTION_COUNT: usize = 3;
+pub const SHOP_SELL_RATIO: f32 = 0.4;
+pub const SHOP_STAT_SCALING_PER_WAVE: f32 = 0.05;
+pub const RARITY_BONUS_PER_WAVE: f32 = 0.01;
+
+// Crafting
+pub const RECIPE_BOOK_PATH: &str =
+    "embedded://fishmans_adventure/../assets/recipes/workbench.recipes.ron";
+pub const SALVAGE_BASE_AMOUNT: u32 = 2;
+
+// Armor
+pub const ARMOR_DAMAGE_PER_DURABILITY: u32 = 10;
+pub const ARMOR_LOW_DURABILITY: f32 = 0.25;
+pub const ARMOR_REPAIR_COST_PER_POINT: f32 = 0.5;
+
+// Coins
+pub const COIN_BASE_DROP: u32 = 2;
+pub const COIN_DROP_PER_WAVE: f32 = 0.25;
+pub const ELITE_COIN_MULTIPLIER: u32 = 3;
+pub const BOSS_COIN_MULTIPLIER: u32 = 10;
+pub const MAX_COINS_PER_DROP: u32 = 5;
+pub const COIN_SIZE: f32 = 10.0;
+pub const COIN_CARRY_OVER: f32 = 0.25;
+
+// Status effects
+pub const STATUS_TICK_INTERVAL: f32 = 0.5;
+pub const TRAIL_DEBUFF_DURATION: f32 = 3.0;
+pub const TRAIL_DEBUFF_SLOW: i32 = 3;
+pub const TRAIL_DEBUFF_POISON: u32 = 1;
+pub const TRAIL_DEBUFF_MAX_STACKS: u32 = 3;
+pub const REGENERATION_MAX_STACKS: u32 = 5;
+pub const THROWABLE_RANGE: f32 = 300.0;
+pub const THROWABLE_RADIUS: f32 = 60.0;
+pub const EFFECT_ICON_SIZE: f32 = 40.0;
+
+// Combat feedback
+pub const HEALTH_BAR_MIN_WIDTH: f32 = 20.0;
+pub const HEALTH_BAR_MAX_WIDTH: f32 = 60.0;
+pub const HEALTH_BAR_HEIGHT: f32 = 5.0;
+pub const HEALTH_BAR_OFFSET: f32 = 35.0;
+pub const MAX_DAMAGE_NUMBERS: usize = 200;
+pub const DAMAGE_NUMBER_LIFETIME: f32 = 0.6;
+
+// Knockback
+pub const KNOCKBACK_DAMPING: f32 = 8.0;
+pub const KNOCKBACK_MIN_SPEED: f32 = 5.0;
+pub const BULLET_KNOCKBACK: f32 = 150.0;
+pub const BULLET_HIT_STUN: f32 = 0.05;
+pub const CONTACT_KNOCKBACK: f32 = 500.0;
+pub const CONTACT_HIT_STUN: f32 = 0.15;
+pub const EXPLOSION_KNOCKBACK: f32 = 800.0;
+pub const EXPLOSION_HIT_STUN: f32 = 0.3;
+
+// Collision
+pub const COLLISION_CELL_SIZE: f32 = 64.0;
+// Contact distances are the sum of two radii: player-enemy 50, bullet hits 30
+pub const PLAYER_COLLIDER_RADIUS: f32 = 25.0;
+pub const ENEMY_COLLIDER_RADIUS: f32 = 25.0;
+pub const BULLET_COLLIDER_RADIUS: f32 = 5.0;
+// Enemies keep their spacing independent of their contact radius
+pub const ENEMY_SEPARATION_RADIUS: f32 = 15.0;
+// With the player's radius this keeps the portal usable within 60 of its center
+pub const PORTAL_INTERACT_RADIUS: f32 = 35.0;
+// Trails hurt once the player's body touches the patch, not only its center
+pub const TRAIL_HALF_SIZE: f32 = 10.0;
+pub const CHARGE_WALL_DAMAGE: u32 = 20;
+pub const CHARGE_WALL_STUN: f32 = 1.0;
+
+// Enemy index
+pub const ENEMY_INDEX_CELL_SIZE: f32 = 64.0;
 
 // Gun
 pub const BULLET_SPAWN_INTERVAL: f32 = 0.2;
@@ -39,6 +170,8 @@ pub const BULLET_SPEED: u32 = 15;
 pub const BULLET_DAMAGE: u32 = 55;
 pub const BULLET_SPREAD: f32 = 0.7;
 pub const NUM_BULLETS_PER_SHOT: usize = 5;
+pub const BULLET_CRIT_CHANCE: f32 = 0.1;
+pub const BULLET_CRIT_MULTIPLIER: u32 = 2;
 
 // Colors
 pub const BG_COLOR: (u8, u8, u8) = (197, 204, 184);
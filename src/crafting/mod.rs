@@ -0,0 +1,605 @@
+pub mod recipe;
+
+use bevy::prelude::*;
+
+use crate::dialog::{close_dialog, ActiveDialog, DialogAction, DialogActionEvent};
+use crate::gun::{Attachments, BulletStats, GunStats, WeaponSummary};
+use crate::inventory::{
+    consume_one, InventoryChangedEvent, Item, ItemCategory, PlayerInventory, Rarity, SlotId,
+};
+use crate::items::{give_item, item_definitions, ItemKind};
+use crate::player::Player;
+use crate::resources::Wave;
+use crate::ron_asset::RonAssetLoader;
+use crate::state::GameState;
+use crate::town_screen::{
+    close_town_screen, list_sections, navigate_town_screen, spawn_town_screen, town_screen_closed,
+    TownScreenMode,
+};
+use crate::*;
+
+pub use self::recipe::*;
+
+pub struct CraftingPlugin;
+
+/// A town bench where gear is salvaged and recipes are crafted.
+#[derive(Component)]
+pub struct Workbench;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum WorkbenchMode {
+    #[default]
+    Craft,
+    Salvage,
+}
+
+impl TownScreenMode for WorkbenchMode {
+    fn other(self) -> Self {
+        match self {
+            WorkbenchMode::Craft => WorkbenchMode::Salvage,
+            WorkbenchMode::Salvage => WorkbenchMode::Craft,
+        }
+    }
+}
+
+/// Whether the crafting screen is open. `selected` indexes the known recipes
+/// when crafting and the salvageable bag items when salvaging.
+#[derive(Resource, Default)]
+pub struct WorkbenchState {
+    pub open: bool,
+    pub mode: WorkbenchMode,
+    pub selected: usize,
+    pub message: String,
+}
+
+#[derive(Resource, Default)]
+pub struct Recipes(pub Handle<RecipeBook>);
+
+#[derive(Component)]
+struct WorkbenchTitle;
+#[derive(Component)]
+struct WorkbenchList(WorkbenchMode);
+#[derive(Component)]
+struct WorkbenchDetails;
+
+type WeaponQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static mut GunStats,
+        &'static mut BulletStats,
+        &'static mut Attachments,
+    ),
+>;
+
+impl Plugin for CraftingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<RecipeBook>()
+            .init_asset_loader::<RonAssetLoader<RecipeBook>>()
+            .init_resource::<WorkbenchState>()
+            .init_resource::<Recipes>()
+            .add_systems(Startup, load_recipe_book)
+            .add_systems(
+                Update,
+                (
+                    (handle_workbench_input, update_workbench_display).run_if(workbench_open),
+                    open_workbench,
+                )
+                    .chain()
+                    .after(close_dialog)
+                    .run_if(in_state(GameState::Town)),
+            );
+    }
+}
+
+fn workbench_open(workbench: Res<WorkbenchState>) -> bool {
+    workbench.open
+}
+
+fn load_recipe_book(asset_server: Res<AssetServer>, mut recipes: ResMut<Recipes>) {
+    recipes.0 = asset_server.load(RECIPE_BOOK_PATH);
+}
+
+/// Materials from breaking down `item`, more of them the rarer it is. Only
+/// guns and armor can be salvaged.
+pub fn salvage_yield(item: &Item) -> Vec<(&'static str, u32)> {
+    let material = match item.category {
+        ItemCategory::Weapon => "Scrap Metal",
+        ItemCategory::Armor(_) => "Fabric",
+        _ => return Vec::new(),
+    };
+    let amount = (SALVAGE_BASE_AMOUNT as f32 * item.rarity.price_multiplier()).round() as u32;
+    let mut materials = vec![(material, amount)];
+    // One dust per step above common
+    let dust = item.rarity as u32;
+    if dust > 0 {
+        materials.push(("Arcane Dust", dust));
+    }
+    materials
+}
+
+/// Each ingredient of `recipe` with how many usable ones are in `bag` and how
+/// many are needed.
+fn ingredient_counts<'a>(recipe: &'a Recipe, bag: &[&Item]) -> Vec<(&'a str, u32, u32)> {
+    recipe
+        .ingredients
+        .iter()
+        .map(|(name, needed)| {
+            let have = bag
+                .iter()
+                .filter(|item| recipe.uses(name, item))
+                .map(|item| item.stack)
+                .sum();
+            (name.as_str(), have, *needed)
+        })
+        .collect()
+}
+
+/// Ingredients only come from the bag, so nothing worn or on the hotbar is
+/// used up.
+fn bag_items<'a>(
+    inventory: &PlayerInventory,
+    item: impl Fn(Entity) -> Option<&'a Item>,
+) -> Vec<&'a Item> {
+    inventory
+        .bag
+        .iter()
+        .flatten()
+        .filter_map(|entity| item(*entity))
+        .collect()
+}
+
+fn salvageable_slots(
+    inventory: &PlayerInventory,
+    item_query: &Query<&mut Item>,
+) -> Vec<(SlotId, Entity)> {
+    inventory
+        .bag_slots()
+        .filter(|(_, entity)| {
+            item_query
+                .get(*entity)
+                .is_ok_and(|item| !salvage_yield(item).is_empty())
+        })
+        .collect()
+}
+
+fn known_recipes(book: Option<&RecipeBook>, wave: u32) -> Vec<&Recipe> {
+    book.map(|book| {
+        book.recipes
+            .iter()
+            .filter(|recipe| recipe.unlock_wave <= wave)
+            .collect()
+    })
+    .unwrap_or_default()
+}
+
+/// Opens the crafting screen when the workbench's dialog asks for it.
+fn open_workbench(
+    mut commands: Commands,
+    mut ev_action: EventReader<DialogActionEvent>,
+    workbench_query: Query<(), With<Workbench>>,
+    mut active_dialog: ResMut<ActiveDialog>,
+    mut workbench: ResMut<WorkbenchState>,
+    font: Res<UiFont>,
+) {
+    for event in ev_action.read() {
+        let (DialogAction::OpenWorkbench, Some(npc)) = (&event.action, event.npc) else {
+            continue;
+        };
+        if active_dialog.0.is_some() || !workbench_query.contains(npc) {
+            continue;
+        }
+        *workbench = WorkbenchState {
+            open: true,
+            ..default()
+        };
+        active_dialog.0 = Some(spawn_workbench_screen(&mut commands, &font.0));
+    }
+}
+
+fn spawn_workbench_screen(commands: &mut Commands, font: &Handle<Font>) -> Entity {
+    let layout = spawn_town_screen(
+        commands,
+        font,
+        300.0,
+        3,
+        "Up/Down: select  Left/Right: craft/salvage  Enter: confirm  Esc: leave",
+    );
+    commands.entity(layout.title).insert(WorkbenchTitle);
+    commands
+        .entity(layout.columns[0])
+        .insert(WorkbenchList(WorkbenchMode::Craft));
+    commands
+        .entity(layout.columns[1])
+        .insert(WorkbenchList(WorkbenchMode::Salvage));
+    commands.entity(layout.columns[2]).insert(WorkbenchDetails);
+    layout.root
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn handle_workbench_input(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut workbench: ResMut<WorkbenchState>,
+    mut active_dialog: ResMut<ActiveDialog>,
+    mut player_query: Query<&mut PlayerInventory, With<Player>>,
+    mut item_query: Query<&mut Item>,
+    mut weapon_query: WeaponQuery,
+    recipes: Res<Recipes>,
+    books: Res<Assets<RecipeBook>>,
+    wave: Res<Wave>,
+    handle: Res<GlobalTextureAtlas>,
+    mut ev_changed: EventWriter<InventoryChangedEvent>,
+) {
+    if town_screen_closed(&keyboard_input) {
+        close_town_screen(&mut commands, &mut *workbench, &mut active_dialog);
+        return;
+    }
+    let Ok(mut inventory) = player_query.get_single_mut() else {
+        close_town_screen(&mut commands, &mut *workbench, &mut active_dialog);
+        return;
+    };
+
+    let recipes = known_recipes(books.get(&recipes.0), wave.number);
+    let salvageable = salvageable_slots(&inventory, &item_query);
+    let workbench = &mut *workbench;
+    let Some(selected) = navigate_town_screen(
+        &keyboard_input,
+        &mut workbench.mode,
+        &mut workbench.selected,
+        |mode| match mode {
+            WorkbenchMode::Craft => recipes.len(),
+            WorkbenchMode::Salvage => salvageable.len(),
+        },
+    ) else {
+        return;
+    };
+    workbench.message = match workbench.mode {
+        WorkbenchMode::Craft => craft(
+            &mut commands,
+            recipes[selected],
+            &mut inventory,
+            &mut item_query,
+            &mut weapon_query,
+            &handle,
+            wave.number,
+            &mut ev_changed,
+        ),
+        WorkbenchMode::Salvage => {
+            let (slot, entity) = salvageable[selected];
+            salvage(
+                &mut commands,
+                slot,
+                entity,
+                &mut inventory,
+                &item_query,
+                &handle,
+                wave.number,
+                &mut ev_changed,
+            )
+        }
+    };
+}
+
+/// Uses up the ingredients of `recipe` and makes its result. Returns what
+/// happened, for the screen to show.
+#[allow(clippy::too_many_arguments)]
+fn craft(
+    commands: &mut Commands,
+    recipe: &Recipe,
+    inventory: &mut PlayerInventory,
+    item_query: &mut Query<&mut Item>,
+    weapon_query: &mut WeaponQuery,
+    handle: &GlobalTextureAtlas,
+    wave_number: u32,
+    ev_changed: &mut EventWriter<InventoryChangedEvent>,
+) -> String {
+    let bag = bag_items(inventory, |entity| item_query.get(entity).ok());
+    let missing = ingredient_counts(recipe, &bag)
+        .into_iter()
+        .any(|(_, have, needed)| have < needed);
+    if missing {
+        return "Missing ingredients".to_string();
+    }
+
+    match &recipe.result {
+        RecipeResult::Item {
+            name,
+            rarity,
+            amount,
+        } => {
+            let Some(definition) = item_definitions()
+                .into_iter()
+                .find(|definition| definition.name == name)
+            else {
+                return format!("Unknown item {name}");
+            };
+            give_item(commands, handle, &definition, *rarity, wave_number, *amount);
+        }
+        RecipeResult::Attachment(attachment) => {
+            let Some(weapon) = inventory.weapon else {
+                return "Equip a weapon first".to_string();
+            };
+            let weapon_name = item_query
+                .get(weapon)
+                .map(|item| item.name.clone())
+                .unwrap_or_default();
+            let Ok((mut gun_stats, mut bullet_stats, mut attachments)) =
+                weapon_query.get_mut(weapon)
+            else {
+                return "Equip a weapon first".to_string();
+            };
+            if attachments.0.contains(attachment) {
+                return format!("Your {} already has a {}", weapon_name, attachment.name());
+            }
+            attachment.apply(&mut gun_stats, &mut bullet_stats);
+            attachments.0.push(*attachment);
+        }
+    }
+
+    for (name, amount) in recipe.ingredients.iter() {
+        for _ in 0..*amount {
+            // Cheapest copies go first
+            let slot = (0..inventory.bag.len())
+                .map(SlotId::Bag)
+                .filter_map(|slot| Some((slot, item_query.get(inventory.get(slot)?).ok()?)))
+                .filter(|(_, item)| recipe.uses(name, item))
+                .min_by_key(|(_, item)| item.rarity)
+                .map(|(slot, _)| slot);
+            if let Some(slot) = slot {
+                consume_one(commands, inventory, slot, item_query, ev_changed);
+            }
+        }
+    }
+    format!("Crafted {}", recipe.name())
+}
+
+/// Breaks the item in `slot` down into materials.
+#[allow(clippy::too_many_arguments)]
+fn salvage(
+    commands: &mut Commands,
+    slot: SlotId,
+    entity: Entity,
+    inventory: &mut PlayerInventory,
+    item_query: &Query<&mut Item>,
+    handle: &GlobalTextureAtlas,
+    wave_number: u32,
+    ev_changed: &mut EventWriter<InventoryChangedEvent>,
+) -> String {
+    let Ok(item) = item_query.get(entity) else {
+        return String::new();
+    };
+    let materials = salvage_yield(item);
+    let definitions = item_definitions();
+    for (name, amount) in materials.iter() {
+        if let Some(definition) = definitions
+            .iter()
+            .find(|definition| definition.name == *name)
+        {
+            // Materials are always common so they stack
+            give_item(
+                commands,
+                handle,
+                definition,
+                Rarity::Common,
+                wave_number,
+                *amount,
+            );
+        }
+    }
+    let message = format!("Salvaged {} into {}", item.name, describe_yield(&materials));
+    inventory.set(slot, None, ev_changed);
+    commands.entity(entity).despawn_recursive();
+    message
+}
+
+fn describe_yield(materials: &[(&str, u32)]) -> String {
+    materials
+        .iter()
+        .map(|(name, amount)| format!("{} {}", amount, name))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn update_workbench_display(
+    workbench: Res<WorkbenchState>,
+    player_query: Query<&PlayerInventory, With<Player>>,
+    item_query: Query<&Item>,
+    weapon_query: Query<(&GunStats, &BulletStats, &Attachments)>,
+    recipes: Res<Recipes>,
+    books: Res<Assets<RecipeBook>>,
+    wave: Res<Wave>,
+    mut title_query: Query<&mut Text, With<WorkbenchTitle>>,
+    mut list_query: Query<(&mut Text, &WorkbenchList), Without<WorkbenchTitle>>,
+    mut details_query: Query<
+        &mut Text,
+        (
+            With<WorkbenchDetails>,
+            Without<WorkbenchTitle>,
+            Without<WorkbenchList>,
+        ),
+    >,
+    font: Res<UiFont>,
+) {
+    let Ok(inventory) = player_query.get_single() else {
+        return;
+    };
+    let style = |color: Color| TextStyle {
+        font: font.0.clone(),
+        font_size: 18.0,
+        color,
+    };
+    let grey = Color::srgb(0.6, 0.6, 0.6);
+    let red = Color::srgb(1.0, 0.3, 0.3);
+    let green = Color::srgb(0.3, 1.0, 0.3);
+
+    if let Ok(mut title) = title_query.get_single_mut() {
+        title.sections[0].value = format!("Workbench\n{}", workbench.message);
+    }
+
+    let recipes = known_recipes(books.get(&recipes.0), wave.number);
+    let bag = bag_items(inventory, |entity| item_query.get(entity).ok());
+    let craftable = |recipe: &Recipe| {
+        ingredient_counts(recipe, &bag)
+            .into_iter()
+            .all(|(_, have, needed)| have >= needed)
+    };
+    let salvageable = inventory
+        .bag
+        .iter()
+        .flatten()
+        .filter_map(|entity| item_query.get(*entity).ok())
+        .filter(|item| !salvage_yield(item).is_empty())
+        .collect::<Vec<_>>();
+
+    for (mut text, list) in list_query.iter_mut() {
+        let (heading, entries) = match list.0 {
+            WorkbenchMode::Craft => (
+                "Craft",
+                recipes
+                    .iter()
+                    .map(|recipe| (recipe.name(), craftable(recipe)))
+                    .collect::<Vec<_>>(),
+            ),
+            WorkbenchMode::Salvage => (
+                "Salvage",
+                salvageable
+                    .iter()
+                    .map(|item| (format!("{} ({:?})", item.name, item.rarity), true))
+                    .collect::<Vec<_>>(),
+            ),
+        };
+        text.sections = list_sections(
+            &font.0,
+            heading,
+            list.0 == workbench.mode,
+            workbench.selected,
+            entries,
+        );
+    }
+
+    let Ok(mut details) = details_query.get_single_mut() else {
+        return;
+    };
+    let mut sections = Vec::new();
+    match workbench.mode {
+        WorkbenchMode::Craft => {
+            if let Some(recipe) = recipes.get(workbench.selected) {
+                sections.push(TextSection::new("Needs\n", style(Color::WHITE)));
+                for (name, have, needed) in ingredient_counts(recipe, &bag) {
+                    sections.push(TextSection::new(
+                        format!("  {} {}/{}\n", name, have, needed),
+                        style(if have >= needed { green } else { red }),
+                    ));
+                }
+                sections.push(TextSection::new("\nMakes\n", style(Color::WHITE)));
+                let weapon = inventory.weapon.and_then(|weapon| {
+                    Some((item_query.get(weapon).ok()?, weapon_query.get(weapon).ok()?))
+                });
+                for (line, color) in preview(recipe, weapon, wave.number) {
+                    let color = if color { Color::WHITE } else { red };
+                    sections.push(TextSection::new(format!("  {}\n", line), style(color)));
+                }
+            }
+        }
+        WorkbenchMode::Salvage => {
+            if let Some(item) = salvageable.get(workbench.selected) {
+                sections.push(TextSection::new("Breaks down into\n", style(Color::WHITE)));
+                for (name, amount) in salvage_yield(item) {
+                    sections.push(TextSection::new(
+                        format!("  {} {}\n", amount, name),
+                        style(green),
+                    ));
+                }
+            }
+        }
+    }
+    if sections.is_empty() {
+        sections.push(TextSection::new("Nothing selected", style(grey)));
+    }
+    details.sections = sections;
+}
+
+/// Lines describing what `recipe` makes, each with whether it is fine
+/// (true) or a problem (false).
+fn preview(
+    recipe: &Recipe,
+    weapon: Option<(&Item, (&GunStats, &BulletStats, &Attachments))>,
+    wave_number: u32,
+) -> Vec<(String, bool)> {
+    match &recipe.result {
+        RecipeResult::Item {
+            name,
+            rarity,
+            amount,
+        } => {
+            let mut lines = vec![(format!("{} x{} ({:?})", name, amount, rarity), true)];
+            let Some(definition) = item_definitions()
+                .into_iter()
+                .find(|definition| definition.name == name)
+            else {
+                return lines;
+            };
+            let summary = match definition.kind.scaled(*rarity, wave_number) {
+                ItemKind::Gun {
+                    gun_stats,
+                    bullet_stats,
+                    ..
+                } => {
+                    let summary = WeaponSummary::new(&gun_stats, &bullet_stats);
+                    format!("{:.0} DPS, {:.0} range", summary.dps, summary.range)
+                }
+                ItemKind::Armor(armor_stats) => {
+                    let mut stats = vec![
+                        format!("{} DEF", armor_stats.defense),
+                        format!("{} durability", armor_stats.max_durability),
+                    ];
+                    stats.extend(
+                        armor_stats
+                            .modifiers
+                            .iter()
+                            .map(|(stat, amount)| stat.describe(*amount)),
+                    );
+                    stats.join(", ")
+                }
+                ItemKind::Potion {
+                    potion_type,
+                    potion_stats,
+                } => potion_type.description(&potion_stats),
+                ItemKind::Material(_) => "Crafting material".to_string(),
+            };
+            lines.push((summary, true));
+            lines
+        }
+        RecipeResult::Attachment(attachment) => {
+            let mut lines = vec![(format!("{} attachment", attachment.name()), true)];
+            let Some((item, (gun_stats, bullet_stats, attachments))) = weapon else {
+                lines.push(("Needs an equipped weapon".to_string(), false));
+                return lines;
+            };
+            if attachments.0.contains(attachment) {
+                lines.push((format!("Already fitted to your {}", item.name), false));
+                return lines;
+            }
+            let (mut fitted_gun, mut fitted_bullet) = (gun_stats.clone(), bullet_stats.clone());
+            attachment.apply(&mut fitted_gun, &mut fitted_bullet);
+            let before = WeaponSummary::new(gun_stats, bullet_stats);
+            let after = WeaponSummary::new(&fitted_gun, &fitted_bullet);
+            lines.push((format!("Fitted to your {}:", item.name), true));
+            lines.push((format!("DPS {:.0} -> {:.0}", before.dps, after.dps), true));
+            lines.push((
+                format!("Range {:.0} -> {:.0}", before.range, after.range),
+                true,
+            ));
+            lines.push((
+                format!(
+                    "Spread {:.1} -> {:.1}",
+                    before.spread_degrees, after.spread_degrees
+                ),
+                true,
+            ));
+            lines
+        }
+    }
+}
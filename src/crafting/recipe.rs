@@ -0,0 +1,97 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::gun::GunAttachment;
+use crate::inventory::{Item, Rarity};
+use crate::items::item_definitions;
+use crate::ron_asset::RonAsset;
+
+/// Everything the workbench can make, loaded from a `.recipes.ron` file.
+#[derive(Asset, TypePath, Deserialize, Debug)]
+pub struct RecipeBook {
+    pub recipes: Vec<Recipe>,
+}
+
+/// Ingredients are item names with how many are used up. A recipe is known
+/// once the run has reached `unlock_wave`.
+#[derive(Deserialize, Debug)]
+pub struct Recipe {
+    pub ingredients: Vec<(String, u32)>,
+    pub result: RecipeResult,
+    #[serde(default)]
+    pub unlock_wave: u32,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub enum RecipeResult {
+    /// Creates `amount` of an item from `item_definitions()` by name.
+    Item {
+        name: String,
+        #[serde(default)]
+        rarity: Rarity,
+        #[serde(default = "one")]
+        amount: u32,
+    },
+    /// Fits the attachment to the equipped weapon.
+    Attachment(GunAttachment),
+}
+
+fn one() -> u32 {
+    1
+}
+
+impl Recipe {
+    pub fn name(&self) -> String {
+        match &self.result {
+            RecipeResult::Item { name, rarity, .. } if *rarity != Rarity::Common => {
+                format!("{} ({:?})", name, rarity)
+            }
+            RecipeResult::Item { name, .. } => name.clone(),
+            RecipeResult::Attachment(attachment) => attachment.name().to_string(),
+        }
+    }
+
+    /// Whether `item` can be used up for the ingredient `name`. Gear is only
+    /// taken for an upgrade of itself when it is of a lower rarity than the
+    /// result.
+    pub fn uses(&self, name: &str, item: &Item) -> bool {
+        if item.name != name {
+            return false;
+        }
+        match &self.result {
+            RecipeResult::Item {
+                name: result,
+                rarity,
+                ..
+            } if *result == item.name => item.rarity < *rarity,
+            _ => true,
+        }
+    }
+}
+
+impl RonAsset for RecipeBook {
+    const NAME: &'static str = "recipe book";
+    const EXTENSIONS: &'static [&'static str] = &["recipes.ron"];
+
+    // Catch typos in item names at load time rather than at the workbench
+    fn validate(&self) -> Result<(), String> {
+        let definitions = item_definitions();
+        let results = self
+            .recipes
+            .iter()
+            .filter_map(|recipe| match &recipe.result {
+                RecipeResult::Item { name, .. } => Some(name),
+                RecipeResult::Attachment(_) => None,
+            });
+        let ingredients = self
+            .recipes
+            .iter()
+            .flat_map(|recipe| recipe.ingredients.iter().map(|(name, _)| name));
+        for name in results.chain(ingredients) {
+            if !definitions.iter().any(|definition| definition.name == name) {
+                return Err(format!("recipe book refers to unknown item {name:?}"));
+            }
+        }
+        Ok(())
+    }
+}
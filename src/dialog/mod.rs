@@ -0,0 +1,628 @@
+pub mod script;
+
+use std::collections::HashMap;
+
+use bevy::asset::LoadState;
+use bevy::input::mouse::{MouseScrollUnit, MouseWheel};
+use bevy::prelude::*;
+
+use crate::armor::{total_repair_cost, ArmorStats};
+use crate::inventory::{Item, PlayerInventory, Rarity};
+use crate::items::{give_item, item_definitions};
+use crate::player::{Health, Player};
+use crate::portal::Portal;
+use crate::resources::Wave;
+use crate::ron_asset::RonAssetLoader;
+use crate::state::GameState;
+use crate::wallet::{CoinReason, Wallet, WalletAction};
+use crate::*;
+
+pub use self::script::*;
+
+pub struct DialogPlugin;
+
+/// Every script under `assets/dialogs`, by file name without extension.
+const DIALOG_SCRIPTS: [&str; 6] = [
+    "portal",
+    "town_exit",
+    "merchant",
+    "blacksmith",
+    "elder",
+    "workbench",
+];
+
+impl Plugin for DialogPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<DialogScript>()
+            .init_asset_loader::<RonAssetLoader<DialogScript>>()
+            .add_event::<ShowDialogEvent>()
+            .add_event::<CloseDialogEvent>()
+            .add_event::<DialogActionEvent>()
+            .add_systems(Startup, load_dialog_scripts)
+            .add_systems(
+                Update,
+                (
+                    show_dialog,
+                    build_dialog_view,
+                    type_dialog_text,
+                    handle_dialog_navigation,
+                    scroll_dialog_choices,
+                    cancel_dialog,
+                    handle_dialog_confirmation,
+                    apply_dialog_actions,
+                    close_dialog,
+                )
+                    .chain()
+                    .run_if(
+                        in_state(GameState::InGame)
+                            .or_else(in_state(GameState::Paused))
+                            .or_else(in_state(GameState::Town)),
+                    ),
+            )
+            .init_resource::<ActiveDialog>()
+            .init_resource::<DialogReturnState>()
+            .init_resource::<DialogScripts>()
+            .init_resource::<SelectedOption>();
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct ActiveDialog(pub Option<Entity>);
+
+/// Index into the choices currently on offer.
+#[derive(Resource, Default)]
+pub struct SelectedOption(pub usize);
+
+pub fn no_active_dialog(active_dialog: Res<ActiveDialog>) -> bool {
+    active_dialog.0.is_none()
+}
+
+/// The state to go to once the active dialog closes. Confirming an option
+/// may change it before closing.
+#[derive(Resource)]
+pub struct DialogReturnState(pub GameState);
+
+impl Default for DialogReturnState {
+    fn default() -> Self {
+        Self(GameState::InGame)
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct DialogScripts(pub HashMap<String, Handle<DialogScript>>);
+
+/// The dialog box. It is rebuilt whenever `node` changes, which also covers
+/// a script that was still loading when the dialog opened.
+#[derive(Component)]
+pub struct Conversation {
+    pub script: Handle<DialogScript>,
+    pub node: String,
+    pub npc: Option<Entity>,
+    /// Indices into the node's choices that passed their conditions.
+    choices: Vec<usize>,
+    built: bool,
+}
+
+#[derive(Component)]
+struct DialogText {
+    full: String,
+    shown: f32,
+}
+
+impl DialogText {
+    fn finished(&self) -> bool {
+        self.shown as usize >= self.full.chars().count()
+    }
+}
+
+#[derive(Component, Default)]
+struct DialogChoiceList {
+    offset: f32,
+}
+
+#[derive(Component)]
+struct DialogChoiceButton(usize);
+
+#[derive(Event)]
+pub struct ShowDialogEvent {
+    pub script: String,
+    pub npc: Option<Entity>,
+}
+
+impl ShowDialogEvent {
+    pub fn new(script: &str) -> Self {
+        Self {
+            script: script.to_string(),
+            npc: None,
+        }
+    }
+
+    pub fn with_npc(mut self, npc: Entity) -> Self {
+        self.npc = Some(npc);
+        self
+    }
+}
+
+#[derive(Event)]
+pub struct CloseDialogEvent;
+
+/// Sent for every action of a confirmed choice. Actions that belong to
+/// another system, like opening a shop, are handled where that system lives.
+#[derive(Event, Clone, Debug)]
+pub struct DialogActionEvent {
+    pub action: DialogAction,
+    pub npc: Option<Entity>,
+}
+
+fn load_dialog_scripts(asset_server: Res<AssetServer>, mut scripts: ResMut<DialogScripts>) {
+    for name in DIALOG_SCRIPTS {
+        let handle = asset_server.load(format!("{DIALOG_SCRIPT_DIR}/{name}.dialog.ron"));
+        scripts.0.insert(name.to_string(), handle);
+    }
+}
+
+fn show_dialog(
+    mut commands: Commands,
+    mut ev_show_dialog: EventReader<ShowDialogEvent>,
+    mut active_dialog: ResMut<ActiveDialog>,
+    scripts: Res<DialogScripts>,
+    mut return_state: ResMut<DialogReturnState>,
+    current_state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for event in ev_show_dialog.read() {
+        if active_dialog.0.is_some() {
+            continue;
+        }
+        let Some(script) = scripts.0.get(&event.script) else {
+            warn!("No dialog script named {:?}", event.script);
+            continue;
+        };
+        let dialog_entity = commands
+            .spawn((
+                NodeBundle {
+                    style: Style {
+                        width: Val::Percent(100.0),
+                        height: Val::Px(DIALOG_BOX_HEIGHT),
+                        position_type: PositionType::Absolute,
+                        bottom: Val::Px(0.0),
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        justify_content: JustifyContent::Center,
+                        row_gap: Val::Px(8.0),
+                        ..default()
+                    },
+                    background_color: Color::srgba(0.1, 0.1, 0.1, 0.9).into(),
+                    z_index: ZIndex::Global(10),
+                    ..default()
+                },
+                Conversation {
+                    script: script.clone(),
+                    // Filled in from the script once it has loaded
+                    node: String::new(),
+                    npc: event.npc,
+                    choices: Vec::new(),
+                    built: false,
+                },
+            ))
+            .id();
+
+        // The town is safe, so only combat needs pausing
+        return_state.0 = *current_state.get();
+        if return_state.0 == GameState::InGame {
+            next_state.set(GameState::Paused);
+        }
+        active_dialog.0 = Some(dialog_entity);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn build_dialog_view(
+    mut commands: Commands,
+    mut conversation_query: Query<(Entity, &mut Conversation)>,
+    scripts: Res<Assets<DialogScript>>,
+    font: Res<UiFont>,
+    wallet: Res<Wallet>,
+    wave: Res<Wave>,
+    player_query: Query<&PlayerInventory, With<Player>>,
+    item_query: Query<&Item>,
+    armor_query: Query<(&ArmorStats, &Item)>,
+    mut selected_option: ResMut<SelectedOption>,
+    asset_server: Res<AssetServer>,
+    mut ev_close_dialog: EventWriter<CloseDialogEvent>,
+) {
+    for (entity, mut conversation) in conversation_query.iter_mut() {
+        if conversation.built {
+            continue;
+        }
+        let Some(script) = scripts.get(&conversation.script) else {
+            // A broken script never loads, so give up instead of staying paused
+            if let Some(LoadState::Failed(err)) = asset_server.get_load_state(&conversation.script)
+            {
+                warn!("Closing dialog, its script failed to load: {err}");
+                ev_close_dialog.send(CloseDialogEvent);
+            }
+            continue;
+        };
+        if conversation.node.is_empty() {
+            conversation.node = script.start.clone();
+        }
+        let Some(node) = script.nodes.get(&conversation.node) else {
+            warn!("Closing dialog, it has no node {:?}", conversation.node);
+            ev_close_dialog.send(CloseDialogEvent);
+            continue;
+        };
+
+        let inventory = player_query.get_single().ok();
+        let item_names = inventory
+            .map(|inventory| {
+                inventory
+                    .slots()
+                    .filter_map(|(_, item)| item_query.get(item).ok())
+                    .map(|item| item.name.clone())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        let repair_cost = inventory.map_or(0, |inventory| {
+            total_repair_cost(
+                inventory
+                    .slots()
+                    .filter_map(|(_, item)| armor_query.get(item).ok()),
+            )
+        });
+        let context = DialogContext {
+            coins: wallet.coins,
+            wave: wave.number,
+            item_names: &item_names,
+            repair_cost,
+        };
+        conversation.choices = node.available_choices(&context);
+        conversation.built = true;
+        selected_option.0 = 0;
+
+        let text_style = |font_size: f32, color: Color| TextStyle {
+            font: font.0.clone(),
+            font_size,
+            color,
+        };
+        commands.entity(entity).despawn_descendants();
+        commands.entity(entity).with_children(|parent| {
+            if !node.speaker.is_empty() {
+                parent.spawn(TextBundle::from_section(
+                    node.speaker.clone(),
+                    text_style(24.0, Color::srgb(1.0, 0.9, 0.4)),
+                ));
+            }
+            parent.spawn((
+                TextBundle::from_section("", text_style(18.0, Color::WHITE)).with_style(Style {
+                    max_width: Val::Percent(80.0),
+                    ..default()
+                }),
+                DialogText {
+                    full: context.fill(&node.text),
+                    shown: 0.0,
+                },
+            ));
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        height: Val::Px(DIALOG_CHOICE_STEP * DIALOG_VISIBLE_CHOICES as f32),
+                        flex_direction: FlexDirection::Column,
+                        overflow: Overflow::clip_y(),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent
+                        .spawn((
+                            NodeBundle {
+                                style: Style {
+                                    flex_direction: FlexDirection::Column,
+                                    align_items: AlignItems::Center,
+                                    ..default()
+                                },
+                                ..default()
+                            },
+                            DialogChoiceList::default(),
+                        ))
+                        .with_children(|parent| {
+                            for (i, choice) in conversation.choices.iter().enumerate() {
+                                spawn_dialog_button(
+                                    parent,
+                                    &context.fill(&node.choices[*choice].text),
+                                    DialogChoiceButton(i),
+                                    &font.0,
+                                );
+                            }
+                        });
+                });
+        });
+    }
+}
+
+fn spawn_dialog_button(
+    parent: &mut ChildBuilder,
+    text: &str,
+    button: DialogChoiceButton,
+    handle: &Handle<Font>,
+) {
+    parent
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    width: Val::Px(320.0),
+                    height: Val::Px(DIALOG_CHOICE_STEP - 8.0),
+                    margin: UiRect::vertical(Val::Px(4.0)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: BackgroundColor(Color::srgb(0.5, 0.5, 0.5)),
+                ..default()
+            },
+            button,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                text,
+                TextStyle {
+                    font: handle.clone(),
+                    font_size: 18.0,
+                    color: Color::WHITE,
+                },
+            ));
+        });
+}
+
+/// Reveals the node text a few characters at a time.
+fn type_dialog_text(time: Res<Time>, mut text_query: Query<(&mut Text, &mut DialogText)>) {
+    for (mut text, mut dialog_text) in text_query.iter_mut() {
+        if !dialog_text.finished() {
+            dialog_text.shown += DIALOG_CHARS_PER_SECOND * time.delta_seconds();
+        }
+        let shown = dialog_text.shown as usize;
+        if text.sections[0].value.chars().count() != shown.min(dialog_text.full.chars().count()) {
+            text.sections[0].value = dialog_text.full.chars().take(shown).collect();
+        }
+    }
+}
+
+fn handle_dialog_navigation(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut selected_option: ResMut<SelectedOption>,
+    interaction_query: Query<(&Interaction, &DialogChoiceButton), Changed<Interaction>>,
+    mut button_query: Query<(&DialogChoiceButton, &mut BackgroundColor)>,
+) {
+    let count = button_query.iter().count();
+    if count == 0 {
+        return;
+    }
+    if keyboard_input.just_pressed(KeyCode::ArrowUp) {
+        selected_option.0 = (selected_option.0 + count - 1) % count;
+    }
+    if keyboard_input.just_pressed(KeyCode::ArrowDown) {
+        selected_option.0 = (selected_option.0 + 1) % count;
+    }
+    for (interaction, button) in interaction_query.iter() {
+        if *interaction == Interaction::Hovered {
+            selected_option.0 = button.0;
+        }
+    }
+
+    for (button, mut color) in button_query.iter_mut() {
+        if button.0 == selected_option.0 {
+            *color = BackgroundColor(Color::srgb(0.0, 0.8, 0.0));
+        } else {
+            *color = BackgroundColor(Color::srgb(0.5, 0.5, 0.5));
+        }
+    }
+}
+
+/// Scrolls the choice list with the mouse wheel, and keeps the selected
+/// choice in view when it changes from the keyboard.
+fn scroll_dialog_choices(
+    mut ev_scroll: EventReader<MouseWheel>,
+    selected_option: Res<SelectedOption>,
+    button_query: Query<(), With<DialogChoiceButton>>,
+    mut list_query: Query<(&mut DialogChoiceList, &mut Style)>,
+) {
+    let Ok((mut list, mut style)) = list_query.get_single_mut() else {
+        ev_scroll.clear();
+        return;
+    };
+    let visible_height = DIALOG_CHOICE_STEP * DIALOG_VISIBLE_CHOICES as f32;
+    let content_height = DIALOG_CHOICE_STEP * button_query.iter().count() as f32;
+    let mut offset = list.offset;
+
+    for event in ev_scroll.read() {
+        offset += match event.unit {
+            MouseScrollUnit::Line => event.y * DIALOG_CHOICE_STEP,
+            MouseScrollUnit::Pixel => event.y,
+        };
+    }
+    if selected_option.is_changed() {
+        let top = DIALOG_CHOICE_STEP * selected_option.0 as f32;
+        offset = offset
+            .min(-top + visible_height - DIALOG_CHOICE_STEP)
+            .max(-top);
+    }
+
+    let offset = offset.clamp(-(content_height - visible_height).max(0.0), 0.0);
+    if offset != list.offset {
+        list.offset = offset;
+        style.top = Val::Px(offset);
+    }
+}
+
+/// Escape leaves a conversation without picking a choice.
+fn cancel_dialog(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    active_dialog: Res<ActiveDialog>,
+    mut ev_close_dialog: EventWriter<CloseDialogEvent>,
+) {
+    if active_dialog.0.is_some() && keyboard_input.just_pressed(KeyCode::Escape) {
+        ev_close_dialog.send(CloseDialogEvent);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_dialog_confirmation(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    interaction_query: Query<(&Interaction, &DialogChoiceButton), Changed<Interaction>>,
+    mut selected_option: ResMut<SelectedOption>,
+    active_dialog: Res<ActiveDialog>,
+    mut conversation_query: Query<&mut Conversation>,
+    mut text_query: Query<&mut DialogText>,
+    scripts: Res<Assets<DialogScript>>,
+    mut ev_action: EventWriter<DialogActionEvent>,
+    mut ev_close_dialog: EventWriter<CloseDialogEvent>,
+) {
+    let clicked = interaction_query
+        .iter()
+        .find(|(interaction, _)| **interaction == Interaction::Pressed)
+        .map(|(_, button)| button.0);
+    if !keyboard_input.just_pressed(KeyCode::Enter) && clicked.is_none() {
+        return;
+    }
+    let Some(mut conversation) = active_dialog
+        .0
+        .and_then(|entity| conversation_query.get_mut(entity).ok())
+    else {
+        return;
+    };
+    if !conversation.built {
+        return;
+    }
+
+    // The first press skips the typing effect
+    if let Ok(mut text) = text_query.get_single_mut() {
+        if !text.finished() {
+            text.shown = text.full.chars().count() as f32;
+            return;
+        }
+    }
+    if let Some(index) = clicked {
+        selected_option.0 = index;
+    }
+
+    let choice = scripts
+        .get(&conversation.script)
+        .and_then(|script| script.nodes.get(&conversation.node))
+        .zip(conversation.choices.get(selected_option.0))
+        .map(|(node, index)| &node.choices[*index]);
+    let Some(choice) = choice else {
+        // A node without any choice left on offer just ends the conversation
+        ev_close_dialog.send(CloseDialogEvent);
+        return;
+    };
+
+    for action in choice.actions.iter() {
+        ev_action.send(DialogActionEvent {
+            action: action.clone(),
+            npc: conversation.npc,
+        });
+    }
+    match &choice.next {
+        Some(next) => {
+            conversation.node = next.clone();
+            conversation.built = false;
+        }
+        None => {
+            ev_close_dialog.send(CloseDialogEvent);
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn apply_dialog_actions(
+    mut commands: Commands,
+    mut ev_action: EventReader<DialogActionEvent>,
+    mut return_state: ResMut<DialogReturnState>,
+    mut wave: ResMut<Wave>,
+    portal_query: Query<Entity, With<Portal>>,
+    mut player_query: Query<&mut Health, With<Player>>,
+    handle: Res<GlobalTextureAtlas>,
+    mut ev_wallet: EventWriter<WalletAction>,
+) {
+    for event in ev_action.read() {
+        match &event.action {
+            DialogAction::GiveItem(name) => {
+                let Some(definition) = item_definitions()
+                    .into_iter()
+                    .find(|definition| definition.name == name)
+                else {
+                    warn!("Dialog tried to give unknown item {:?}", name);
+                    continue;
+                };
+                give_item(
+                    &mut commands,
+                    &handle,
+                    &definition,
+                    Rarity::Common,
+                    wave.number,
+                    1,
+                );
+            }
+            DialogAction::SpendCoins(amount) => {
+                ev_wallet.send(WalletAction::Spend {
+                    amount: *amount,
+                    reason: CoinReason::Dialog,
+                });
+            }
+            DialogAction::Heal(amount) => {
+                if let Ok(mut health) = player_query.get_single_mut() {
+                    health.0 += amount;
+                }
+            }
+            DialogAction::Travel(Destination::Town) => return_state.0 = GameState::Town,
+            DialogAction::Travel(Destination::Arena) => return_state.0 = GameState::InGame,
+            DialogAction::StartNewWave => {
+                start_new_wave_cycle(&mut wave, &mut commands, &portal_query);
+            }
+            DialogAction::OpenShop | DialogAction::RepairArmor | DialogAction::OpenWorkbench => {}
+        }
+    }
+}
+
+pub fn close_dialog(
+    mut commands: Commands,
+    mut ev_close_dialog: EventReader<CloseDialogEvent>,
+    mut active_dialog: ResMut<ActiveDialog>,
+    conversation_query: Query<(), With<Conversation>>,
+    return_state: Res<DialogReturnState>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if ev_close_dialog.is_empty() {
+        return;
+    }
+    ev_close_dialog.clear();
+    // Other screens borrow `ActiveDialog` to block input, so only close
+    // conversations
+    let Some(entity) = active_dialog
+        .0
+        .filter(|entity| conversation_query.contains(*entity))
+    else {
+        return;
+    };
+    commands.entity(entity).despawn_recursive();
+    active_dialog.0 = None;
+    next_state.set(return_state.0);
+}
+
+fn start_new_wave_cycle(
+    wave: &mut Wave,
+    commands: &mut Commands,
+    portal_query: &Query<Entity, With<Portal>>,
+) {
+    wave.number += 1;
+    wave.enemies_total = crate::utils::calculate_enemies_per_wave(wave.number);
+    wave.enemies_left = wave.enemies_total;
+    wave.enemies_spawned = 0;
+    wave.requires_portal = false;
+    wave.portal_spawned = false;
+
+    for portal_entity in portal_query.iter() {
+        commands.entity(portal_entity).despawn();
+    }
+}
@@ -0,0 +1,127 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::ron_asset::RonAsset;
+
+/// A conversation loaded from a `.dialog.ron` file. Each node shows a line of
+/// text and the choices the player can answer with.
+#[derive(Asset, TypePath, Deserialize, Debug)]
+pub struct DialogScript {
+    pub start: String,
+    pub nodes: HashMap<String, DialogNode>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct DialogNode {
+    #[serde(default)]
+    pub speaker: String,
+    pub text: String,
+    pub choices: Vec<DialogChoice>,
+}
+
+/// An answer the player can pick. It is only offered when every condition
+/// holds; picking it runs its actions in order, then moves to `next` or ends
+/// the conversation when there is none.
+#[derive(Deserialize, Debug)]
+pub struct DialogChoice {
+    pub text: String,
+    #[serde(default)]
+    pub conditions: Vec<DialogCondition>,
+    #[serde(default)]
+    pub actions: Vec<DialogAction>,
+    #[serde(default)]
+    pub next: Option<String>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub enum DialogCondition {
+    MinCoins(u32),
+    WaveReached(u32),
+    HasItem(String),
+    /// Some carried armor is below full durability.
+    NeedsRepair,
+    CanAffordRepair,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub enum DialogAction {
+    /// Creates a common item from `item_definitions()` by name.
+    GiveItem(String),
+    SpendCoins(u32),
+    Heal(u32),
+    /// Opens the trade screen of the NPC being talked to.
+    OpenShop,
+    /// Restores all carried armor for `{repair_cost}` coins.
+    RepairArmor,
+    /// Opens the crafting screen of the workbench being used.
+    OpenWorkbench,
+    Travel(Destination),
+    StartNewWave,
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Destination {
+    Town,
+    Arena,
+}
+
+/// What conditions are checked against.
+pub struct DialogContext<'a> {
+    pub coins: u32,
+    pub wave: u32,
+    pub item_names: &'a [String],
+    pub repair_cost: u32,
+}
+
+impl DialogContext<'_> {
+    /// Replaces placeholders like `{repair_cost}` in node and choice text.
+    pub fn fill(&self, text: &str) -> String {
+        text.replace("{repair_cost}", &self.repair_cost.to_string())
+    }
+}
+
+impl DialogCondition {
+    pub fn holds(&self, context: &DialogContext) -> bool {
+        match self {
+            DialogCondition::MinCoins(amount) => context.coins >= *amount,
+            DialogCondition::WaveReached(wave) => context.wave >= *wave,
+            DialogCondition::HasItem(name) => context.item_names.iter().any(|item| item == name),
+            DialogCondition::NeedsRepair => context.repair_cost > 0,
+            DialogCondition::CanAffordRepair => context.coins >= context.repair_cost,
+        }
+    }
+}
+
+impl DialogNode {
+    /// Indices into `choices` of the answers currently on offer.
+    pub fn available_choices(&self, context: &DialogContext) -> Vec<usize> {
+        self.choices
+            .iter()
+            .enumerate()
+            .filter(|(_, choice)| choice.conditions.iter().all(|c| c.holds(context)))
+            .map(|(i, _)| i)
+            .collect()
+    }
+}
+
+impl RonAsset for DialogScript {
+    const NAME: &'static str = "dialog script";
+    const EXTENSIONS: &'static [&'static str] = &["dialog.ron"];
+
+    // Catch typos in node ids at load time rather than mid-conversation
+    fn validate(&self) -> Result<(), String> {
+        let targets = self
+            .nodes
+            .values()
+            .flat_map(|node| node.choices.iter())
+            .filter_map(|choice| choice.next.as_ref());
+        for id in std::iter::once(&self.start).chain(targets) {
+            if !self.nodes.contains_key(id) {
+                return Err(format!("dialog script refers to unknown node {id:?}"));
+            }
+        }
+        Ok(())
+    }
+}
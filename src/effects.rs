@@ -0,0 +1,403 @@
+use bevy::prelude::*;
+use std::time::Duration;
+
+use crate::enemy::StatusKind;
+use crate::player::{Defense, Health, Speed};
+use crate::potion::PotionType;
+use crate::state::GameState;
+use crate::utils::safe_subtract;
+use crate::STATUS_TICK_INTERVAL;
+
+pub struct EffectsPlugin;
+
+/// Speed, defense and max health change by flat amounts, the rest by
+/// percentages.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Stat {
+    Speed,
+    /// Movement speed of entities without a flat `Speed`, such as enemies.
+    MoveSpeed,
+    Defense,
+    MaxHealth,
+    FireRate,
+    Damage,
+    PickupRadius,
+    /// Share of contact damage dealt back to the enemy.
+    Thorns,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StackingRule {
+    /// Reapplying resets the duration.
+    Refresh,
+    /// Reapplying adds a stack up to the limit and resets the duration.
+    Stack(u32),
+    /// Keeps the stronger modifiers and the longer remaining duration.
+    KeepStrongest,
+    /// Reapplying while active does nothing.
+    Ignore,
+}
+
+/// Applied once per `STATUS_TICK_INTERVAL` for every stack.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TickEffect {
+    Heal(u32),
+    Damage(u32),
+}
+
+/// Who applied an effect. Reapplying from the same source follows the
+/// effect's stacking rule; effects from different sources always coexist.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EffectSource {
+    Potion(PotionType),
+    Armor,
+    LevelUp(u32),
+    Trail,
+    Status(StatusKind),
+}
+
+#[derive(Clone, Debug)]
+pub struct Effect {
+    pub source: EffectSource,
+    /// Changes to stats per stack, negative for debuffs.
+    pub modifiers: Vec<(Stat, i32)>,
+    pub tick: Option<TickEffect>,
+    /// Stunned entities neither move nor act.
+    pub stuns: bool,
+    /// Effects without a duration last until they are removed.
+    pub duration: Option<f32>,
+    pub stacking: StackingRule,
+}
+
+struct ActiveEffect {
+    effect: Effect,
+    remaining: Option<Timer>,
+    tick_timer: Timer,
+    stacks: u32,
+}
+
+#[derive(Component, Default)]
+pub struct ActiveEffects(Vec<ActiveEffect>);
+
+/// The stats of an entity before any effect.
+#[derive(Component, Clone, Copy)]
+pub struct BaseStats {
+    pub speed: u32,
+    pub defense: u32,
+}
+
+/// Health granted by `Stat::MaxHealth`. Player health has no cap, so gaining
+/// the bonus heals by the difference and losing it takes the difference back.
+#[derive(Component, Default)]
+pub struct BonusHealth(pub u32);
+
+#[derive(Event, Clone, Debug)]
+pub enum EffectAction {
+    Apply {
+        target: Entity,
+        effect: Effect,
+    },
+    Remove {
+        target: Entity,
+        source: EffectSource,
+    },
+    /// Removes every debuff.
+    Cleanse {
+        target: Entity,
+    },
+}
+
+/// A timed effect as shown on the HUD.
+pub struct EffectTimer {
+    pub source: EffectSource,
+    pub remaining: f32,
+    pub fraction_left: f32,
+    pub stacks: u32,
+}
+
+impl Plugin for EffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<EffectAction>().add_systems(
+            Update,
+            (
+                apply_effect_actions,
+                tick_effects.run_if(in_state(GameState::InGame)),
+                update_stats_from_effects,
+                update_bonus_health,
+            )
+                .chain(),
+        );
+    }
+}
+
+impl Stat {
+    /// E.g. "+2 speed" or "+25% thorns".
+    pub fn describe(&self, amount: i32) -> String {
+        let (unit, name) = match self {
+            Stat::Speed => ("", "speed"),
+            Stat::MoveSpeed => ("%", "move speed"),
+            Stat::Defense => ("", "defense"),
+            Stat::MaxHealth => ("", "max health"),
+            Stat::FireRate => ("%", "fire rate"),
+            Stat::Damage => ("%", "damage"),
+            Stat::PickupRadius => ("%", "pickup radius"),
+            Stat::Thorns => ("%", "thorns"),
+        };
+        format!("{:+}{} {}", amount, unit, name)
+    }
+}
+
+impl EffectSource {
+    pub fn tint(&self) -> Color {
+        match self {
+            EffectSource::Potion(potion_type) => potion_type.tint(),
+            EffectSource::Armor => Color::srgb(0.6, 0.6, 0.65),
+            EffectSource::LevelUp(_) => Color::srgb(1.0, 0.85, 0.3),
+            EffectSource::Trail => Color::srgb(0.5, 0.9, 0.2),
+            EffectSource::Status(kind) => kind.tint(),
+        }
+    }
+}
+
+impl Effect {
+    pub fn new(source: EffectSource, duration: Option<f32>) -> Self {
+        Self {
+            source,
+            modifiers: Vec::new(),
+            tick: None,
+            stuns: false,
+            duration,
+            stacking: StackingRule::Refresh,
+        }
+    }
+
+    pub fn with_modifier(mut self, stat: Stat, amount: i32) -> Self {
+        self.modifiers.push((stat, amount));
+        self
+    }
+
+    pub fn with_tick(mut self, tick: TickEffect) -> Self {
+        self.tick = Some(tick);
+        self
+    }
+
+    pub fn with_stun(mut self) -> Self {
+        self.stuns = true;
+        self
+    }
+
+    pub fn with_stacking(mut self, stacking: StackingRule) -> Self {
+        self.stacking = stacking;
+        self
+    }
+
+    pub fn is_debuff(&self) -> bool {
+        self.modifiers.iter().any(|(_, amount)| *amount < 0)
+            || matches!(self.tick, Some(TickEffect::Damage(_)))
+            || self.stuns
+    }
+
+    fn strength(&self) -> i32 {
+        self.modifiers.iter().map(|(_, amount)| amount.abs()).sum()
+    }
+
+    fn timer(&self) -> Option<Timer> {
+        self.duration
+            .map(|duration| Timer::from_seconds(duration, TimerMode::Once))
+    }
+}
+
+impl ActiveEffects {
+    pub fn apply(&mut self, effect: Effect) {
+        let Some(active) = self
+            .0
+            .iter_mut()
+            .find(|active| active.effect.source == effect.source)
+        else {
+            self.0.push(ActiveEffect {
+                remaining: effect.timer(),
+                effect,
+                tick_timer: Timer::from_seconds(STATUS_TICK_INTERVAL, TimerMode::Repeating),
+                stacks: 1,
+            });
+            return;
+        };
+
+        match effect.stacking {
+            StackingRule::Refresh => {
+                active.remaining = effect.timer();
+                active.effect = effect;
+            }
+            StackingRule::Stack(max_stacks) => {
+                active.stacks = (active.stacks + 1).min(max_stacks);
+                active.remaining = effect.timer();
+                active.effect = effect;
+            }
+            StackingRule::KeepStrongest => {
+                let remaining = match (&active.remaining, effect.duration) {
+                    (Some(timer), Some(duration)) => Some(Timer::from_seconds(
+                        timer.remaining_secs().max(duration),
+                        TimerMode::Once,
+                    )),
+                    _ => None,
+                };
+                active.remaining = remaining;
+                if effect.strength() >= active.effect.strength() {
+                    active.effect.modifiers = effect.modifiers;
+                }
+            }
+            StackingRule::Ignore => {}
+        }
+    }
+
+    pub fn remove(&mut self, source: EffectSource) {
+        self.0.retain(|active| active.effect.source != source);
+    }
+
+    pub fn cleanse(&mut self) {
+        self.0.retain(|active| !active.effect.is_debuff());
+    }
+
+    /// Ticks every effect and returns the healing and damage over time dealt
+    /// this frame, and whether any effect ticked or expired.
+    pub fn tick(&mut self, delta: Duration) -> (u32, u32, bool) {
+        let (mut heal, mut damage, mut ticked) = (0, 0, false);
+        for active in self.0.iter_mut() {
+            if let Some(remaining) = active.remaining.as_mut() {
+                remaining.tick(delta);
+            }
+            let Some(tick) = active.effect.tick else {
+                continue;
+            };
+            active.tick_timer.tick(delta);
+            let ticks = active.tick_timer.times_finished_this_tick() * active.stacks;
+            ticked |= ticks > 0;
+            match tick {
+                TickEffect::Heal(amount) => heal += amount * ticks,
+                TickEffect::Damage(amount) => damage += amount * ticks,
+            }
+        }
+        let count = self.0.len();
+        self.0
+            .retain(|active| !active.remaining.as_ref().is_some_and(Timer::finished));
+        (heal, damage, ticked || self.0.len() != count)
+    }
+
+    /// The total change to `stat` from every active effect.
+    pub fn modifier(&self, stat: Stat) -> i32 {
+        self.0
+            .iter()
+            .flat_map(|active| {
+                active
+                    .effect
+                    .modifiers
+                    .iter()
+                    .filter(move |(modified, _)| *modified == stat)
+                    .map(move |(_, amount)| amount * active.stacks as i32)
+            })
+            .sum()
+    }
+
+    /// Scales `value` by the percentage change to `stat`.
+    pub fn scale(&self, stat: Stat, value: f32) -> f32 {
+        value * (1.0 + self.modifier(stat) as f32 / 100.0).max(0.0)
+    }
+
+    pub fn has(&self, source: EffectSource) -> bool {
+        self.0.iter().any(|active| active.effect.source == source)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn is_stunned(&self) -> bool {
+        self.0.iter().any(|active| active.effect.stuns)
+    }
+
+    /// The tint of the most recently applied effect.
+    pub fn tint(&self) -> Option<Color> {
+        self.0.last().map(|active| active.effect.source.tint())
+    }
+
+    pub fn timers(&self) -> impl Iterator<Item = EffectTimer> + '_ {
+        self.0.iter().filter_map(|active| {
+            let timer = active.remaining.as_ref()?;
+            Some(EffectTimer {
+                source: active.effect.source,
+                remaining: timer.remaining_secs(),
+                fraction_left: 1.0 - timer.fraction(),
+                stacks: active.stacks,
+            })
+        })
+    }
+}
+
+fn apply_effect_actions(
+    mut actions: EventReader<EffectAction>,
+    mut effects_query: Query<&mut ActiveEffects>,
+) {
+    for action in actions.read() {
+        match action {
+            EffectAction::Apply { target, effect } => {
+                if let Ok(mut effects) = effects_query.get_mut(*target) {
+                    effects.apply(effect.clone());
+                }
+            }
+            EffectAction::Remove { target, source } => {
+                if let Ok(mut effects) = effects_query.get_mut(*target) {
+                    effects.remove(*source);
+                }
+            }
+            EffectAction::Cleanse { target } => {
+                if let Ok(mut effects) = effects_query.get_mut(*target) {
+                    effects.cleanse();
+                }
+            }
+        }
+    }
+}
+
+fn tick_effects(time: Res<Time>, mut query: Query<(&mut ActiveEffects, &mut Health)>) {
+    for (mut effects, mut health) in query.iter_mut() {
+        if effects.is_empty() {
+            continue;
+        }
+        // Timers advance every frame; only report a change when something
+        // happened, so `Changed<ActiveEffects>` readers don't run every frame.
+        let (heal, damage, changed) = effects.bypass_change_detection().tick(time.delta());
+        if changed {
+            effects.set_changed();
+        }
+        health.0 = safe_subtract(health.0 + heal, damage);
+    }
+}
+
+fn update_stats_from_effects(
+    mut query: Query<
+        (&BaseStats, &ActiveEffects, &mut Speed, &mut Defense),
+        Changed<ActiveEffects>,
+    >,
+) {
+    for (base, effects, mut speed, mut defense) in query.iter_mut() {
+        speed.0 = modified(base.speed, effects.modifier(Stat::Speed), 1);
+        defense.0 = modified(base.defense, effects.modifier(Stat::Defense), 0);
+    }
+}
+
+fn update_bonus_health(
+    mut query: Query<(&ActiveEffects, &mut BonusHealth, &mut Health), Changed<ActiveEffects>>,
+) {
+    for (effects, mut bonus, mut health) in query.iter_mut() {
+        let target = effects.modifier(Stat::MaxHealth).max(0) as u32;
+        if target == bonus.0 {
+            continue;
+        }
+        health.0 = (health.0 + target).saturating_sub(bonus.0).max(1);
+        bonus.0 = target;
+    }
+}
+
+fn modified(value: u32, modifier: i32, min: u32) -> u32 {
+    (value as i32 + modifier).max(min as i32) as u32
+}
@@ -1,6 +1,11 @@
 use super::components::*;
+use super::elite::{Elite, EliteAffix};
 use super::types::EnemyType;
 use crate::animation::AnimationTimer;
+use crate::collision::{Collider, CollisionLayer};
+use crate::effects::ActiveEffects;
+use crate::knockback::{Knockback, Mass};
+use crate::loot::{loot_pool_for, strong_enemies_bundle, LootPool};
 use crate::world::InGameEntity;
 use bevy::prelude::*;
 
@@ -13,6 +18,10 @@ pub struct EnemyBundle {
     pub animation_timer: AnimationTimer,
     pub in_game_entity: InGameEntity,
     pub collider: Collider,
+    pub active_effects: ActiveEffects,
+    pub knockback: Knockback,
+    pub mass: Mass,
+    pub loot_pool: LootPool,
 }
 
 impl EnemyBundle {
@@ -22,9 +31,11 @@ impl EnemyBundle {
         handle: &Res<crate::GlobalTextureAtlas>,
     ) -> Self {
         let config = enemy_type.get_config();
+        let loot_pool = loot_pool_for(&enemy_type);
         Self {
             enemy: Enemy {
                 health: config.health,
+                max_health: config.health,
                 speed: config.speed,
                 damage: config.damage,
                 xp: config.xp,
@@ -33,8 +44,12 @@ impl EnemyBundle {
             enemy_type,
             sprite_bundle: SpriteBundle {
                 texture: handle.image.clone().unwrap(),
+                sprite: Sprite {
+                    color: config.color,
+                    ..default()
+                },
                 transform: Transform::from_translation(position)
-                    .with_scale(Vec3::splat(crate::SPRITE_SCALE_FACTOR)),
+                    .with_scale(Vec3::splat(crate::SPRITE_SCALE_FACTOR * config.scale)),
                 ..default()
             },
             texture_atlas: TextureAtlas {
@@ -43,7 +58,24 @@ impl EnemyBundle {
             },
             animation_timer: AnimationTimer(Timer::from_seconds(0.08, TimerMode::Repeating)),
             in_game_entity: InGameEntity,
-            collider: Collider { radius: 15 },
+            collider: Collider::circle(crate::ENEMY_COLLIDER_RADIUS, CollisionLayer::Enemy),
+            active_effects: ActiveEffects::default(),
+            knockback: Knockback::default(),
+            mass: Mass(config.mass),
+            loot_pool,
         }
     }
+
+    pub fn apply_elite(&mut self, elite: &Elite) {
+        self.enemy.health *= crate::ELITE_HEALTH_MULTIPLIER;
+        self.enemy.max_health = self.enemy.health;
+        self.enemy.xp *= crate::ELITE_XP_MULTIPLIER;
+        self.mass.0 *= crate::ELITE_MASS_MULTIPLIER;
+        if elite.has(EliteAffix::Hasted) {
+            self.enemy.speed += self.enemy.speed / 2;
+        }
+        self.sprite_bundle.sprite.color = elite.tint();
+        self.sprite_bundle.transform.scale *= crate::ELITE_SCALE_MULTIPLIER;
+        self.loot_pool = strong_enemies_bundle();
+    }
 }
@@ -4,6 +4,7 @@ use bevy::prelude::*;
 #[derive(Component)]
 pub struct Enemy {
     pub health: u32,
+    pub max_health: u32,
     pub speed: u32,
     pub damage: u32,
     pub xp: u32,
@@ -13,13 +14,25 @@ pub struct Enemy {
 #[derive(Component)]
 pub struct Trail {
     pub damage: u32,
-    pub radius: f32,
 }
 
 #[derive(Component)]
-pub struct Collider {
-    pub radius: u32,
+pub struct EnemyBullet;
+
+#[derive(Event)]
+pub struct EnemyDamagedEvent {
+    pub entity: Entity,
+    pub position: Vec3,
+    pub damage: u32,
+    pub crit: bool,
 }
 
 #[derive(Component)]
-pub struct EnemyBullet;
+pub struct BulletDelay(pub Timer);
+
+#[derive(Component)]
+pub struct BulletAcceleration {
+    pub rate: f32,
+    pub max_speed: f32,
+    pub current_speed: f32,
+}
@@ -0,0 +1,93 @@
+use bevy::prelude::*;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::utils::calculate_elite_chance;
+use crate::{ELITE_REGEN_INTERVAL, ELITE_SHIELD_HITS};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EliteAffix {
+    Shielded,
+    Hasted,
+    Vampiric,
+    Explosive,
+    Regenerating,
+}
+
+#[derive(Component)]
+pub struct Elite {
+    pub affixes: Vec<EliteAffix>,
+    pub shield_hits: u32,
+    pub regen_timer: Timer,
+}
+
+impl EliteAffix {
+    const ALL: [EliteAffix; 5] = [
+        EliteAffix::Shielded,
+        EliteAffix::Hasted,
+        EliteAffix::Vampiric,
+        EliteAffix::Explosive,
+        EliteAffix::Regenerating,
+    ];
+
+    pub fn tint(&self) -> Color {
+        match self {
+            EliteAffix::Shielded => Color::srgb(0.5, 0.7, 1.0),
+            EliteAffix::Hasted => Color::srgb(1.0, 1.0, 0.4),
+            EliteAffix::Vampiric => Color::srgb(1.0, 0.3, 0.3),
+            EliteAffix::Explosive => Color::srgb(1.0, 0.6, 0.2),
+            EliteAffix::Regenerating => Color::srgb(0.4, 1.0, 0.4),
+        }
+    }
+}
+
+impl Elite {
+    pub fn roll(wave_number: u32) -> Option<Self> {
+        let mut rng = rand::thread_rng();
+        if rng.gen::<f32>() >= calculate_elite_chance(wave_number) {
+            return None;
+        }
+
+        let max_affixes = (1 + wave_number as usize / 5).min(EliteAffix::ALL.len());
+        let count = rng.gen_range(1..=max_affixes);
+        let affixes = EliteAffix::ALL
+            .choose_multiple(&mut rng, count)
+            .copied()
+            .collect::<Vec<_>>();
+
+        Some(Self::new(affixes))
+    }
+
+    pub fn new(affixes: Vec<EliteAffix>) -> Self {
+        let shield_hits = if affixes.contains(&EliteAffix::Shielded) {
+            ELITE_SHIELD_HITS
+        } else {
+            0
+        };
+        Self {
+            affixes,
+            shield_hits,
+            regen_timer: Timer::from_seconds(ELITE_REGEN_INTERVAL, TimerMode::Repeating),
+        }
+    }
+
+    pub fn has(&self, affix: EliteAffix) -> bool {
+        self.affixes.contains(&affix)
+    }
+
+    /// Returns true if the hit was absorbed by the shield.
+    pub fn absorb_hit(&mut self) -> bool {
+        if self.shield_hits > 0 {
+            self.shield_hits -= 1;
+            return true;
+        }
+        false
+    }
+
+    pub fn tint(&self) -> Color {
+        self.affixes
+            .first()
+            .map(|affix| affix.tint())
+            .unwrap_or(Color::WHITE)
+    }
+}
@@ -0,0 +1,234 @@
+use bevy::prelude::*;
+use bevy::sprite::Anchor;
+use bevy::utils::HashSet;
+
+use super::components::{Enemy, EnemyDamagedEvent};
+use crate::world::InGameEntity;
+use crate::{Settings, UiFont};
+use crate::{
+    DAMAGE_NUMBER_LIFETIME, HEALTH_BAR_HEIGHT, HEALTH_BAR_MAX_WIDTH, HEALTH_BAR_MIN_WIDTH,
+    HEALTH_BAR_OFFSET, MAX_DAMAGE_NUMBERS,
+};
+
+#[derive(Component)]
+pub struct EnemyHealthBar {
+    pub owner: Entity,
+    pub width: f32,
+}
+
+#[derive(Component)]
+pub struct EnemyHealthBarFill;
+
+#[derive(Component)]
+pub struct HasHealthBar(pub Entity);
+
+#[derive(Component)]
+pub struct DamageNumber {
+    pub timer: Timer,
+}
+
+#[derive(Resource, Default)]
+pub struct DamageNumberPool {
+    pub free: Vec<Entity>,
+    pub spawned: usize,
+}
+
+pub fn reset_damage_number_pool(mut commands: Commands) {
+    commands.insert_resource(DamageNumberPool::default());
+}
+
+pub fn spawn_enemy_health_bars(
+    mut commands: Commands,
+    mut events: EventReader<EnemyDamagedEvent>,
+    enemy_query: Query<&Enemy, Without<HasHealthBar>>,
+    settings: Res<Settings>,
+) {
+    if !settings.show_enemy_health_bars {
+        events.clear();
+        return;
+    }
+
+    let mut handled = HashSet::new();
+    for event in events.read() {
+        let Ok(enemy) = enemy_query.get(event.entity) else {
+            continue;
+        };
+        if enemy.health == 0 || !handled.insert(event.entity) {
+            continue;
+        }
+
+        let width =
+            (enemy.max_health as f32 * 0.3).clamp(HEALTH_BAR_MIN_WIDTH, HEALTH_BAR_MAX_WIDTH);
+        let bar = commands
+            .spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: Color::srgba(0.0, 0.0, 0.0, 0.7),
+                        custom_size: Some(Vec2::new(width, HEALTH_BAR_HEIGHT)),
+                        ..default()
+                    },
+                    transform: Transform::from_translation(
+                        event.position + Vec3::new(0.0, HEALTH_BAR_OFFSET, 20.0),
+                    ),
+                    ..default()
+                },
+                EnemyHealthBar {
+                    owner: event.entity,
+                    width,
+                },
+                InGameEntity,
+            ))
+            .with_children(|parent| {
+                parent.spawn((
+                    SpriteBundle {
+                        sprite: Sprite {
+                            color: Color::srgb(0.8, 0.1, 0.1),
+                            custom_size: Some(Vec2::new(width, HEALTH_BAR_HEIGHT)),
+                            anchor: Anchor::CenterLeft,
+                            ..default()
+                        },
+                        transform: Transform::from_xyz(-width / 2.0, 0.0, 0.1),
+                        ..default()
+                    },
+                    EnemyHealthBarFill,
+                ));
+            })
+            .id();
+        // The enemy may be despawned later this frame, before the command runs.
+        commands.entity(event.entity).try_insert(HasHealthBar(bar));
+    }
+}
+
+pub fn update_enemy_health_bars(
+    mut commands: Commands,
+    mut bar_query: Query<(Entity, &EnemyHealthBar, &mut Transform, &Children)>,
+    mut fill_query: Query<&mut Sprite, With<EnemyHealthBarFill>>,
+    enemy_query: Query<(&Enemy, &Transform), Without<EnemyHealthBar>>,
+    settings: Res<Settings>,
+) {
+    for (bar_entity, bar, mut transform, children) in bar_query.iter_mut() {
+        let Ok((enemy, enemy_transform)) = enemy_query.get(bar.owner) else {
+            commands.entity(bar_entity).despawn_recursive();
+            continue;
+        };
+        if !settings.show_enemy_health_bars || enemy.health == 0 {
+            commands.entity(bar_entity).despawn_recursive();
+            commands.entity(bar.owner).remove::<HasHealthBar>();
+            continue;
+        }
+
+        transform.translation =
+            enemy_transform.translation + Vec3::new(0.0, HEALTH_BAR_OFFSET, 20.0);
+        let ratio = enemy.health as f32 / enemy.max_health.max(1) as f32;
+        for child in children.iter() {
+            if let Ok(mut sprite) = fill_query.get_mut(*child) {
+                sprite.custom_size = Some(Vec2::new(bar.width * ratio, HEALTH_BAR_HEIGHT));
+            }
+        }
+    }
+}
+
+pub fn spawn_damage_numbers(
+    mut commands: Commands,
+    mut events: EventReader<EnemyDamagedEvent>,
+    mut pool: ResMut<DamageNumberPool>,
+    mut number_query: Query<(
+        &mut Text,
+        &mut Transform,
+        &mut Visibility,
+        &mut DamageNumber,
+    )>,
+    settings: Res<Settings>,
+    font: Res<UiFont>,
+) {
+    if !settings.show_damage_numbers {
+        events.clear();
+        return;
+    }
+
+    for event in events.read() {
+        if event.damage == 0 {
+            continue;
+        }
+
+        let (label, style) = damage_number_style(event.damage, event.crit, &font.0);
+        let translation = event.position + Vec3::new(0.0, 30.0, 30.0);
+
+        if let Some(entity) = pool.free.pop() {
+            if let Ok((mut text, mut transform, mut visibility, mut number)) =
+                number_query.get_mut(entity)
+            {
+                *text = Text::from_section(label, style);
+                transform.translation = translation;
+                *visibility = Visibility::Visible;
+                number.timer.reset();
+            }
+        } else if pool.spawned < MAX_DAMAGE_NUMBERS {
+            commands.spawn((
+                Text2dBundle {
+                    text: Text::from_section(label, style),
+                    transform: Transform::from_translation(translation),
+                    ..default()
+                },
+                DamageNumber {
+                    timer: Timer::from_seconds(DAMAGE_NUMBER_LIFETIME, TimerMode::Once),
+                },
+                InGameEntity,
+            ));
+            pool.spawned += 1;
+        }
+    }
+}
+
+fn damage_number_style(damage: u32, crit: bool, font: &Handle<Font>) -> (String, TextStyle) {
+    if crit {
+        (
+            format!("{}!", damage),
+            TextStyle {
+                font: font.clone(),
+                font_size: 40.0,
+                color: Color::srgb(1.0, 0.85, 0.1),
+            },
+        )
+    } else {
+        (
+            damage.to_string(),
+            TextStyle {
+                font: font.clone(),
+                font_size: 28.0,
+                color: Color::WHITE,
+            },
+        )
+    }
+}
+
+pub fn update_damage_numbers(
+    mut pool: ResMut<DamageNumberPool>,
+    mut number_query: Query<(
+        Entity,
+        &mut Text,
+        &mut Transform,
+        &mut Visibility,
+        &mut DamageNumber,
+    )>,
+    time: Res<Time>,
+) {
+    for (entity, mut text, mut transform, mut visibility, mut number) in number_query.iter_mut() {
+        if *visibility == Visibility::Hidden {
+            continue;
+        }
+
+        number.timer.tick(time.delta());
+        if number.timer.finished() {
+            *visibility = Visibility::Hidden;
+            pool.free.push(entity);
+            continue;
+        }
+
+        transform.translation.y += 40.0 * time.delta_seconds();
+        let alpha = 1.0 - number.timer.fraction();
+        for section in text.sections.iter_mut() {
+            section.style.color.set_alpha(alpha);
+        }
+    }
+}
@@ -1,29 +1,57 @@
 pub mod bundles;
 pub mod components;
+pub mod elite;
+pub mod feedback;
+pub mod patterns;
+pub mod status;
 pub mod systems;
 pub mod types;
 
+use crate::collision::{detect_collisions, EnemyIndexSet};
 use crate::state::GameState;
 use bevy::prelude::*;
 
-pub use self::{bundles::*, components::*, systems::*, types::*};
+pub use self::{
+    bundles::*, components::*, elite::*, feedback::*, patterns::*, status::*, systems::*, types::*,
+};
 
 pub struct EnemyPlugin;
 
 impl Plugin for EnemyPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            Update,
-            (
-                spawn_enemies,
-                update_enemy_behavior,
-                despawn_dead_enemies,
-                handle_enemy_collision,
-                handle_shooter_enemies,
-                update_enemy_bullets,
-                handle_enemy_bullet_collision,
+        app.add_event::<EnemyDamagedEvent>()
+            .init_resource::<DamageNumberPool>()
+            .add_systems(OnEnter(GameState::GameInit), reset_damage_number_pool)
+            .add_systems(
+                Update,
+                (
+                    spawn_enemies,
+                    update_enemy_behavior,
+                    despawn_dead_enemies,
+                    handle_enemy_collision,
+                    handle_shooter_enemies,
+                    update_enemy_bullets,
+                    handle_enemy_bullet_collision.after(detect_collisions),
+                    handle_charge_wall_collision.after(detect_collisions),
+                    regenerate_elite_enemies,
+                    handle_summoner_enemies,
+                    handle_healer_enemies.after(EnemyIndexSet),
+                    handle_teleporter_enemies,
+                    apply_status_splashes,
+                    tick_status_effects,
+                    update_status_tint,
+                )
+                    .run_if(in_state(GameState::InGame)),
             )
-                .run_if(in_state(GameState::InGame)),
-        );
+            .add_systems(
+                Update,
+                (
+                    spawn_enemy_health_bars,
+                    update_enemy_health_bars,
+                    spawn_damage_numbers,
+                    update_damage_numbers,
+                )
+                    .run_if(in_state(GameState::InGame)),
+            );
     }
 }
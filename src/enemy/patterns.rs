@@ -0,0 +1,115 @@
+use bevy::prelude::*;
+use rand::Rng;
+use std::f32::consts::TAU;
+
+#[derive(Clone)]
+pub enum BulletPattern {
+    /// Random spread aimed at the player.
+    Spread { count: usize, spread: f32 },
+    /// Evenly spaced ring of bullets.
+    Radial { count: usize },
+    /// Evenly spaced arms that rotate by `step` radians on every shot.
+    Spiral { arms: usize, step: f32 },
+    /// Fan aimed at the player with a fixed angle between bullets.
+    Fan { count: usize, angle: f32 },
+    /// Bullets hang in place for `delay` seconds before moving.
+    Delayed {
+        delay: f32,
+        pattern: Box<BulletPattern>,
+    },
+    /// Bullets gain `rate` speed per second up to `max_speed`.
+    Accelerating {
+        rate: f32,
+        max_speed: f32,
+        pattern: Box<BulletPattern>,
+    },
+    /// Fires every contained pattern at once.
+    Composite(Vec<BulletPattern>),
+}
+
+pub struct EmittedBullet {
+    pub direction: Vec2,
+    pub delay: Option<f32>,
+    pub acceleration: Option<(f32, f32)>,
+}
+
+impl BulletPattern {
+    /// Returns the bullets for one shot. `rotation` is the emitter's running
+    /// angle, advanced by spiral patterns.
+    pub fn emit(&self, aim: Vec2, rotation: &mut f32) -> Vec<EmittedBullet> {
+        let mut bullets = Vec::new();
+        self.emit_into(aim, rotation, &mut bullets);
+        bullets
+    }
+
+    fn emit_into(&self, aim: Vec2, rotation: &mut f32, bullets: &mut Vec<EmittedBullet>) {
+        let aim_angle = aim.y.atan2(aim.x);
+        match self {
+            BulletPattern::Spread { count, spread } => {
+                let mut rng = rand::thread_rng();
+                for _ in 0..*count {
+                    let offset = Vec2::new(
+                        rng.gen_range(-spread..=*spread),
+                        rng.gen_range(-spread..=*spread),
+                    );
+                    bullets.push(EmittedBullet::new((aim + offset).normalize_or_zero()));
+                }
+            }
+            BulletPattern::Radial { count } => {
+                push_ring(bullets, *count, aim_angle);
+            }
+            BulletPattern::Spiral { arms, step } => {
+                push_ring(bullets, *arms, *rotation);
+                *rotation = (*rotation + step) % TAU;
+            }
+            BulletPattern::Fan { count, angle } => {
+                let start = aim_angle - angle * (*count as f32 - 1.0) / 2.0;
+                for i in 0..*count {
+                    bullets.push(EmittedBullet::new(Vec2::from_angle(
+                        start + angle * i as f32,
+                    )));
+                }
+            }
+            BulletPattern::Delayed { delay, pattern } => {
+                let start = bullets.len();
+                pattern.emit_into(aim, rotation, bullets);
+                for bullet in &mut bullets[start..] {
+                    bullet.delay = Some(bullet.delay.unwrap_or(0.0) + delay);
+                }
+            }
+            BulletPattern::Accelerating {
+                rate,
+                max_speed,
+                pattern,
+            } => {
+                let start = bullets.len();
+                pattern.emit_into(aim, rotation, bullets);
+                for bullet in &mut bullets[start..] {
+                    bullet.acceleration = Some((*rate, *max_speed));
+                }
+            }
+            BulletPattern::Composite(patterns) => {
+                for pattern in patterns {
+                    pattern.emit_into(aim, rotation, bullets);
+                }
+            }
+        }
+    }
+}
+
+impl EmittedBullet {
+    fn new(direction: Vec2) -> Self {
+        Self {
+            direction,
+            delay: None,
+            acceleration: None,
+        }
+    }
+}
+
+fn push_ring(bullets: &mut Vec<EmittedBullet>, count: usize, offset: f32) {
+    for i in 0..count {
+        let angle = offset + TAU * i as f32 / count as f32;
+        bullets.push(EmittedBullet::new(Vec2::from_angle(angle)));
+    }
+}
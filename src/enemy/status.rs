@@ -0,0 +1,80 @@
+use bevy::prelude::*;
+
+use crate::effects::{Effect, EffectSource, StackingRule, Stat, TickEffect};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StatusKind {
+    Slow,
+    Burn,
+    Poison,
+    Freeze,
+    Stun,
+}
+
+/// A status as weapons, throwables and potions describe it. Applying one
+/// turns it into an `Effect` on the target's `ActiveEffects`.
+#[derive(Clone, Copy, Debug)]
+pub struct StatusEffect {
+    pub kind: StatusKind,
+    pub duration: f32,
+    /// Damage per tick for burn and poison, speed multiplier for slow.
+    pub magnitude: f32,
+}
+
+/// A thrown status area. Every enemy within `radius` when it lands gets
+/// `effect`, so throwables only need to spawn one.
+#[derive(Component, Clone, Copy)]
+pub struct StatusSplash {
+    pub effect: StatusEffect,
+    pub radius: f32,
+}
+
+impl StatusKind {
+    pub fn stacking_rule(&self) -> StackingRule {
+        match self {
+            StatusKind::Slow => StackingRule::KeepStrongest,
+            StatusKind::Burn => StackingRule::Refresh,
+            StatusKind::Poison => StackingRule::Stack(5),
+            StatusKind::Freeze => StackingRule::Refresh,
+            StatusKind::Stun => StackingRule::Ignore,
+        }
+    }
+
+    pub fn tint(&self) -> Color {
+        match self {
+            StatusKind::Slow => Color::srgb(0.6, 0.6, 0.9),
+            StatusKind::Burn => Color::srgb(1.0, 0.45, 0.2),
+            StatusKind::Poison => Color::srgb(0.5, 0.9, 0.2),
+            StatusKind::Freeze => Color::srgb(0.5, 0.9, 1.0),
+            StatusKind::Stun => Color::srgb(1.0, 1.0, 0.5),
+        }
+    }
+}
+
+impl StatusEffect {
+    pub fn new(kind: StatusKind, duration: f32, magnitude: f32) -> Self {
+        Self {
+            kind,
+            duration,
+            magnitude,
+        }
+    }
+}
+
+impl From<StatusEffect> for Effect {
+    fn from(status: StatusEffect) -> Self {
+        let effect = Effect::new(EffectSource::Status(status.kind), Some(status.duration))
+            .with_stacking(status.kind.stacking_rule());
+        match status.kind {
+            StatusKind::Slow => {
+                let slow = (1.0 - status.magnitude.clamp(0.0, 1.0)) * 100.0;
+                effect.with_modifier(Stat::MoveSpeed, -slow.round() as i32)
+            }
+            StatusKind::Burn | StatusKind::Poison => {
+                effect.with_tick(TickEffect::Damage(status.magnitude as u32))
+            }
+            // Frozen enemies are stunned, only the tint differs
+            StatusKind::Freeze | StatusKind::Stun => effect.with_stun(),
+        }
+    }
+}
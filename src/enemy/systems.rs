@@ -1,18 +1,37 @@
-use super::components::EnemyBullet;
+use super::components::{BulletAcceleration, BulletDelay, EnemyBullet};
 use super::*;
+use crate::collision::{has_line_of_sight, BulletHitPlayer, Collider, CollisionLayer, HitObstacle};
+use crate::effects::{ActiveEffects, EffectAction, Stat};
 use crate::gun::HasLifespan;
 use crate::gun::{BulletDirection, BulletStats};
+use crate::knockback::{Knockback, KnockbackEvent, Mass};
+use crate::loot::{spawn_loot, LootPool};
 use crate::player::{InvincibilityEffect, Player, PlayerDamagedEvent, PlayerLevelingUpEvent};
 use crate::resources::{Level, Wave};
-use crate::utils::get_random_position_around;
-use crate::utils::{calculate_enemies_per_wave, clamp_position};
-use crate::world::InGameEntity;
+use crate::spatial_hash::SpatialHash;
+use crate::spatial_index::EnemyIndex;
+use crate::utils::{
+    calculate_coin_drop, calculate_enemies_per_wave, calculate_loot_chance_multiplier,
+    clamp_position,
+};
+use crate::utils::{get_random_position_around, safe_subtract};
+use crate::wallet::spawn_coins;
+use crate::world::{InGameEntity, Obstacle};
 use crate::GlobalTextureAtlas;
+use crate::MAX_NUM_ENEMIES;
 use crate::PLAYER_INVINCIBLE_TIME;
 use crate::SPAWN_RATE_PER_SECOND;
 use crate::SPRITE_SCALE_FACTOR;
+use crate::{BULLET_COLLIDER_RADIUS, ENEMY_SEPARATION_RADIUS};
+use crate::{CHARGE_WALL_DAMAGE, CHARGE_WALL_STUN};
+use crate::{ELITE_EXPLOSION_DAMAGE, ELITE_EXPLOSION_RADIUS, ELITE_REGEN_AMOUNT};
+use crate::{ENEMY_BULLET_DAMAGE, ENEMY_BULLET_SPEED, ENEMY_BULLET_TIME_SECS};
+use crate::{EXPLOSION_HIT_STUN, EXPLOSION_KNOCKBACK};
+use crate::{SPLITTER_MAX_GENERATION, SPLITTER_SPLIT_COUNT};
 use bevy::prelude::*;
+use bevy::tasks::{ComputeTaskPool, ParallelSlice};
 use bevy::time::Stopwatch;
+use rand::Rng;
 
 use std::time::Duration;
 
@@ -34,9 +53,17 @@ pub fn spawn_enemies(
     for _ in 0..wave.enemies_left.min(SPAWN_RATE_PER_SECOND as u32) {
         let (x, y) = get_random_position_around(player_pos);
         let enemy_type = EnemyType::random();
-        let _config = enemy_type.get_config();
+        let mut bundle = EnemyBundle::new(enemy_type, Vec3::new(x, y, 1.0), &handle);
 
-        commands.spawn(EnemyBundle::new(enemy_type, Vec3::new(x, y, 1.0), &handle));
+        match Elite::roll(wave.number) {
+            Some(elite) => {
+                bundle.apply_elite(&elite);
+                commands.spawn((bundle, elite));
+            }
+            None => {
+                commands.spawn(bundle);
+            }
+        }
 
         wave.enemies_spawned += 1;
     }
@@ -44,7 +71,10 @@ pub fn spawn_enemies(
 
 pub fn update_enemy_behavior(
     player_query: Query<&Transform, With<Player>>,
-    mut enemy_query: Query<(&mut Enemy, &mut Transform), Without<Player>>,
+    mut enemy_query: Query<
+        (&mut Enemy, &mut Transform, &ActiveEffects, &Knockback),
+        Without<Player>,
+    >,
     mut commands: Commands,
     time: Res<Time>,
 ) {
@@ -53,7 +83,10 @@ pub fn update_enemy_behavior(
     }
 
     let player_pos = player_query.single().translation;
-    for (mut enemy, mut transform) in enemy_query.iter_mut() {
+    for (mut enemy, mut transform, status, knockback) in enemy_query.iter_mut() {
+        if status.is_stunned() || knockback.is_stunned() {
+            continue;
+        }
         let speed = enemy.speed;
         let movement = enemy.enemy_type.update_movement(
             transform.translation,
@@ -61,22 +94,85 @@ pub fn update_enemy_behavior(
             speed,
             time.delta(),
         );
-        transform.translation += movement;
+        transform.translation += movement * status.scale(Stat::MoveSpeed, 1.0);
         enemy
             .enemy_type
             .apply(&mut commands, &transform, time.delta());
     }
 }
 
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 pub fn despawn_dead_enemies(
     mut commands: Commands,
-    enemy_query: Query<(&Enemy, Entity), With<Enemy>>,
+    handle: Res<GlobalTextureAtlas>,
+    enemy_query: Query<
+        (
+            &Enemy,
+            Entity,
+            &Transform,
+            Option<&Elite>,
+            Option<&LootPool>,
+        ),
+        With<Enemy>,
+    >,
+    player_query: Query<(Entity, &Transform), (With<Player>, Without<InvincibilityEffect>)>,
     mut wave: ResMut<Wave>,
     mut level: ResMut<Level>,
     mut ew: EventWriter<PlayerLevelingUpEvent>,
+    mut ev_player_damaged: EventWriter<PlayerDamagedEvent>,
+    mut ev_knockback: EventWriter<KnockbackEvent>,
 ) {
-    for (enemy, entity) in enemy_query.iter() {
+    for (enemy, entity, transform, elite, loot_pool) in enemy_query.iter() {
         if enemy.health == 0 {
+            if let Some(loot_pool) = loot_pool {
+                let multiplier = calculate_loot_chance_multiplier(wave.number, elite.is_some());
+                spawn_loot(
+                    &mut commands,
+                    loot_pool,
+                    transform.translation,
+                    multiplier,
+                    &handle,
+                    wave.number,
+                );
+            }
+            // Summoners are the closest thing to a boss
+            let boss = matches!(enemy.enemy_type, EnemyType::Summoner { .. });
+            spawn_coins(
+                &mut commands,
+                transform.translation,
+                calculate_coin_drop(wave.number, elite.is_some(), boss),
+            );
+            if elite.is_some_and(|elite| elite.has(EliteAffix::Explosive)) {
+                spawn_explosion(&mut commands, transform.translation);
+                if let Ok((player_entity, player_transform)) = player_query.get_single() {
+                    let offset = (player_transform.translation - transform.translation).truncate();
+                    if offset.length() <= ELITE_EXPLOSION_RADIUS {
+                        ev_player_damaged.send(PlayerDamagedEvent {
+                            damage: ELITE_EXPLOSION_DAMAGE,
+                        });
+                        ev_knockback.send(KnockbackEvent {
+                            target: player_entity,
+                            impulse: offset.normalize_or_zero() * EXPLOSION_KNOCKBACK,
+                            stun: EXPLOSION_HIT_STUN,
+                        });
+                    }
+                }
+            }
+            if let EnemyType::Splitter { generation } = enemy.enemy_type {
+                if generation < SPLITTER_MAX_GENERATION {
+                    for _ in 0..SPLITTER_SPLIT_COUNT {
+                        let offset = random_offset(20.0, 40.0);
+                        commands.spawn(EnemyBundle::new(
+                            EnemyType::Splitter {
+                                generation: generation + 1,
+                            },
+                            transform.translation + offset,
+                            &handle,
+                        ));
+                    }
+                    add_enemies_to_wave(&mut wave, SPLITTER_SPLIT_COUNT);
+                }
+            }
             commands.entity(entity).despawn();
             wave.enemies_left -= 1;
             if level.add_xp(enemy.xp) {
@@ -100,32 +196,399 @@ pub fn despawn_dead_enemies(
     }
 }
 
-pub fn handle_enemy_collision(mut enemy_query: Query<(Entity, &mut Transform, &Collider)>) {
-    let mut combinations = enemy_query.iter_combinations_mut();
-    while let Some(
-        [(_entity_a, mut transform_a, collider_a), (_entity_b, mut transform_b, collider_b)],
-    ) = combinations.fetch_next()
-    {
-        let distance = transform_a.translation.distance(transform_b.translation);
-        let min_distance = (collider_a.radius + collider_b.radius) as f32;
+fn add_enemies_to_wave(wave: &mut Wave, count: u32) {
+    wave.enemies_left += count;
+    wave.enemies_total += count;
+    wave.enemies_spawned += count;
+}
+
+fn random_offset(min_distance: f32, max_distance: f32) -> Vec3 {
+    let mut rng = rand::thread_rng();
+    let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+    let distance = rng.gen_range(min_distance..max_distance);
+    Vec3::new(angle.cos() * distance, angle.sin() * distance, 0.0)
+}
+
+pub fn handle_summoner_enemies(
+    mut commands: Commands,
+    mut enemy_query: Query<(&mut Enemy, &Transform, &ActiveEffects)>,
+    mut wave: ResMut<Wave>,
+    handle: Res<GlobalTextureAtlas>,
+    time: Res<Time>,
+) {
+    let mut enemy_count = enemy_query.iter().len();
+    for (mut enemy, transform, status) in enemy_query.iter_mut() {
+        if status.is_stunned() {
+            continue;
+        }
+        if let EnemyType::Summoner {
+            ref mut summon_timer,
+            minions_per_summon,
+        } = enemy.enemy_type
+        {
+            summon_timer.tick(time.delta());
+            if !summon_timer.just_finished() || enemy_count >= MAX_NUM_ENEMIES {
+                continue;
+            }
+
+            for _ in 0..minions_per_summon {
+                let offset = random_offset(30.0, 60.0);
+                commands.spawn(EnemyBundle::new(
+                    EnemyType::Basic,
+                    transform.translation + offset,
+                    &handle,
+                ));
+            }
+            add_enemies_to_wave(&mut wave, minions_per_summon);
+            enemy_count += minions_per_summon as usize;
+        }
+    }
+}
+
+pub fn handle_healer_enemies(
+    mut commands: Commands,
+    mut enemy_query: Query<(Entity, &mut Enemy, &Transform, &ActiveEffects)>,
+    index: Res<EnemyIndex>,
+    time: Res<Time>,
+) {
+    let mut heals = Vec::new();
+    for (entity, mut enemy, transform, status) in enemy_query.iter_mut() {
+        if status.is_stunned() {
+            continue;
+        }
+        if let EnemyType::Healer {
+            ref mut heal_timer,
+            heal_amount,
+            heal_radius,
+        } = enemy.enemy_type
+        {
+            heal_timer.tick(time.delta());
+            if heal_timer.just_finished() {
+                let pos = transform.translation;
+                spawn_heal_pulse(&mut commands, pos, heal_radius);
+                for (target, _) in index.within_radius(pos.truncate(), heal_radius) {
+                    if target != entity {
+                        heals.push((target, heal_amount));
+                    }
+                }
+            }
+        }
+    }
+
+    for (target, amount) in heals {
+        if let Ok((_, mut enemy, _, _)) = enemy_query.get_mut(target) {
+            if enemy.health > 0 {
+                enemy.health = (enemy.health + amount).min(enemy.max_health);
+            }
+        }
+    }
+}
+
+fn spawn_heal_pulse(commands: &mut Commands, position: Vec3, radius: f32) {
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::srgba(0.2, 1.0, 0.2, 0.25),
+                custom_size: Some(Vec2::splat(radius * 2.0)),
+                ..default()
+            },
+            transform: Transform::from_translation(position.truncate().extend(0.5)),
+            ..default()
+        },
+        HasLifespan::new(Duration::from_secs_f32(0.3)),
+        InGameEntity,
+    ));
+}
 
-        if distance < min_distance {
-            let overlap = min_distance - distance;
-            let direction = (transform_b.translation - transform_a.translation).normalize();
+pub fn handle_teleporter_enemies(
+    mut commands: Commands,
+    mut enemy_query: Query<(&mut Enemy, &mut Transform, &ActiveEffects), Without<Player>>,
+    player_query: Query<&Transform, With<Player>>,
+    time: Res<Time>,
+) {
+    if player_query.is_empty() {
+        return;
+    }
+    let player_pos = player_query.single().translation;
 
-            transform_a.translation -= direction * overlap * 0.5;
-            transform_b.translation += direction * overlap * 0.5;
+    for (mut enemy, mut transform, status) in enemy_query.iter_mut() {
+        if status.is_stunned() {
+            continue;
+        }
+        if let EnemyType::Teleporter {
+            ref mut state,
+            ref mut teleport_timer,
+            ref mut target_position,
+        } = enemy.enemy_type
+        {
+            teleport_timer.tick(time.delta());
+            if !teleport_timer.just_finished() {
+                continue;
+            }
 
-            clamp_position(&mut transform_a.translation);
-            clamp_position(&mut transform_b.translation);
+            match state {
+                TeleportState::Chasing => {
+                    let mut target = player_pos + random_offset(80.0, 150.0);
+                    clamp_position(&mut target);
+                    spawn_teleport_marker(&mut commands, target);
+                    *target_position = Some(target.truncate());
+                    *state = TeleportState::Telegraphing;
+                    *teleport_timer = Timer::from_seconds(0.8, TimerMode::Once);
+                }
+                TeleportState::Telegraphing => {
+                    if let Some(target) = target_position.take() {
+                        transform.translation = target.extend(transform.translation.z);
+                    }
+                    *state = TeleportState::Recovering;
+                    *teleport_timer = Timer::from_seconds(1.0, TimerMode::Once);
+                }
+                TeleportState::Recovering => {
+                    *state = TeleportState::Chasing;
+                    *teleport_timer = Timer::from_seconds(3.0, TimerMode::Once);
+                }
+            }
         }
     }
 }
 
+fn spawn_teleport_marker(commands: &mut Commands, position: Vec3) {
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::srgba(0.4, 1.0, 1.0, 0.4),
+                custom_size: Some(Vec2::splat(40.0)),
+                ..default()
+            },
+            transform: Transform::from_translation(position.truncate().extend(0.5)),
+            ..default()
+        },
+        HasLifespan::new(Duration::from_secs_f32(0.8)),
+        InGameEntity,
+    ));
+}
+
+fn spawn_explosion(commands: &mut Commands, position: Vec3) {
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::srgba(1.0, 0.5, 0.0, 0.5),
+                custom_size: Some(Vec2::splat(ELITE_EXPLOSION_RADIUS * 2.0)),
+                ..default()
+            },
+            transform: Transform::from_translation(position),
+            ..default()
+        },
+        HasLifespan::new(Duration::from_secs_f32(0.3)),
+        InGameEntity,
+    ));
+}
+
+pub fn regenerate_elite_enemies(mut enemy_query: Query<(&mut Enemy, &mut Elite)>, time: Res<Time>) {
+    for (mut enemy, mut elite) in enemy_query.iter_mut() {
+        if !elite.has(EliteAffix::Regenerating) || enemy.health == 0 {
+            continue;
+        }
+        elite.regen_timer.tick(time.delta());
+        if elite.regen_timer.just_finished() {
+            enemy.health = (enemy.health + ELITE_REGEN_AMOUNT).min(enemy.max_health);
+        }
+    }
+}
+
+pub fn spawn_status_splash(
+    commands: &mut Commands,
+    position: Vec2,
+    effect: StatusEffect,
+    radius: f32,
+) {
+    let mut color = effect.kind.tint();
+    color.set_alpha(0.35);
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color,
+                custom_size: Some(Vec2::splat(radius * 2.0)),
+                ..default()
+            },
+            transform: Transform::from_translation(position.extend(0.5)),
+            ..default()
+        },
+        StatusSplash { effect, radius },
+        HasLifespan::new(Duration::from_secs_f32(0.3)),
+        InGameEntity,
+    ));
+}
+
+pub fn apply_status_splashes(
+    splash_query: Query<(&Transform, &StatusSplash), Added<StatusSplash>>,
+    enemy_query: Query<(Entity, &Transform), With<Enemy>>,
+    mut ev_effect: EventWriter<EffectAction>,
+) {
+    for (splash_transform, splash) in splash_query.iter() {
+        let center = splash_transform.translation.truncate();
+        for (entity, transform) in enemy_query.iter() {
+            if transform.translation.truncate().distance(center) <= splash.radius {
+                ev_effect.send(EffectAction::Apply {
+                    target: entity,
+                    effect: splash.effect.into(),
+                });
+            }
+        }
+    }
+}
+
+pub fn tick_status_effects(
+    mut enemy_query: Query<(Entity, &mut Enemy, &mut ActiveEffects, &Transform)>,
+    mut ev_enemy_damaged: EventWriter<EnemyDamagedEvent>,
+    time: Res<Time>,
+) {
+    for (entity, mut enemy, mut status, transform) in enemy_query.iter_mut() {
+        if status.is_empty() {
+            continue;
+        }
+        let (_, damage, changed) = status.bypass_change_detection().tick(time.delta());
+        if changed {
+            status.set_changed();
+        }
+        if damage > 0 && enemy.health > 0 {
+            enemy.health = safe_subtract(enemy.health, damage);
+            ev_enemy_damaged.send(EnemyDamagedEvent {
+                entity,
+                position: transform.translation,
+                damage,
+                crit: false,
+            });
+        }
+    }
+}
+
+pub fn update_status_tint(
+    mut enemy_query: Query<
+        (&ActiveEffects, &mut Sprite, &EnemyType, Option<&Elite>),
+        Changed<ActiveEffects>,
+    >,
+) {
+    for (status, mut sprite, enemy_type, elite) in enemy_query.iter_mut() {
+        let base_color = match elite {
+            Some(elite) => elite.tint(),
+            None => enemy_type.get_config().color,
+        };
+        let alpha = sprite.color.alpha();
+        sprite.color = status.tint().unwrap_or(base_color).with_alpha(alpha);
+    }
+}
+
+pub fn handle_enemy_collision(
+    mut enemy_query: Query<(Entity, &mut Transform, &Collider, Option<&Mass>), With<Enemy>>,
+) {
+    let bodies = enemy_query
+        .iter()
+        .filter(|(_, _, collider, _)| collider.layer == CollisionLayer::Enemy)
+        .map(|(entity, transform, _, mass)| {
+            (
+                entity,
+                transform.translation.truncate(),
+                ENEMY_SEPARATION_RADIUS,
+                mass.map(|m| m.0).unwrap_or(1.0),
+            )
+        })
+        .collect::<Vec<_>>();
+    if bodies.len() < 2 {
+        return;
+    }
+
+    let max_radius = bodies.iter().map(|body| body.2).fold(0.0, f32::max);
+    let mut grid = SpatialHash::new((max_radius * 2.0).max(1.0));
+    for (index, body) in bodies.iter().enumerate() {
+        grid.insert(index, body.1);
+    }
+
+    // Each body only moves itself, so the pushes can be computed in parallel
+    let indices = (0..bodies.len()).collect::<Vec<_>>();
+    let pushes = indices.par_chunk_map(ComputeTaskPool::get(), 256, |_, chunk| {
+        chunk
+            .iter()
+            .filter_map(|&index| {
+                let (entity, pos, radius, mass) = bodies[index];
+                let mut push = Vec2::ZERO;
+                for other in grid.neighbours(pos) {
+                    if other == index {
+                        continue;
+                    }
+                    let (_, other_pos, other_radius, other_mass) = bodies[other];
+                    let distance = pos.distance(other_pos);
+                    let min_distance = radius + other_radius;
+                    if distance < min_distance {
+                        // Heavier enemies give way less
+                        let share = other_mass / (mass + other_mass);
+                        let direction = (pos - other_pos).normalize_or_zero();
+                        push += direction * (min_distance - distance) * share;
+                    }
+                }
+                (push != Vec2::ZERO).then_some((entity, push))
+            })
+            .collect::<Vec<_>>()
+    });
+
+    for (entity, push) in pushes.into_iter().flatten() {
+        if let Ok((_, mut transform, _, _)) = enemy_query.get_mut(entity) {
+            transform.translation += push.extend(0.0);
+            clamp_position(&mut transform.translation);
+        }
+    }
+}
+
+/// Chargers that slam into a wall mid-charge stop and are stunned.
+pub fn handle_charge_wall_collision(
+    mut enemy_query: Query<(&mut Enemy, &Transform)>,
+    mut ev_obstacle: EventReader<HitObstacle>,
+    mut ev_effect: EventWriter<EffectAction>,
+    mut ev_enemy_damaged: EventWriter<EnemyDamagedEvent>,
+) {
+    for hit in ev_obstacle.read() {
+        if hit.layer != CollisionLayer::Enemy {
+            continue;
+        }
+        let enemy_entity = hit.entity;
+        let Ok((mut enemy, transform)) = enemy_query.get_mut(enemy_entity) else {
+            continue;
+        };
+        let EnemyType::Charge {
+            ref mut state,
+            ref mut charge_timer,
+            ref mut target_position,
+            ..
+        } = enemy.enemy_type
+        else {
+            continue;
+        };
+        if *state != ChargeState::Charging {
+            continue;
+        }
+
+        *state = ChargeState::CoolingDown;
+        *charge_timer = Timer::from_seconds(1.5, TimerMode::Once);
+        *target_position = None;
+        enemy.health = safe_subtract(enemy.health, CHARGE_WALL_DAMAGE);
+
+        ev_enemy_damaged.send(EnemyDamagedEvent {
+            entity: enemy_entity,
+            position: transform.translation,
+            damage: CHARGE_WALL_DAMAGE,
+            crit: false,
+        });
+        ev_effect.send(EffectAction::Apply {
+            target: enemy_entity,
+            effect: StatusEffect::new(StatusKind::Stun, CHARGE_WALL_STUN, 0.0).into(),
+        });
+    }
+}
+
 pub fn handle_shooter_enemies(
     mut commands: Commands,
-    mut enemy_query: Query<(&mut Enemy, &Transform, &mut EnemyType)>,
+    mut enemy_query: Query<(&Transform, &mut EnemyType, &ActiveEffects)>,
     player_query: Query<&Transform, With<Player>>,
+    obstacle_query: Query<(&Transform, &Collider), With<Obstacle>>,
     time: Res<Time>,
     handle: Res<GlobalTextureAtlas>,
 ) {
@@ -134,12 +597,17 @@ pub fn handle_shooter_enemies(
     }
     let player_pos = player_query.single().translation;
 
-    for (_enemy, transform, mut enemy_type) in enemy_query.iter_mut() {
+    for (transform, mut enemy_type, status) in enemy_query.iter_mut() {
+        if status.is_stunned() {
+            continue;
+        }
         if let EnemyType::Shooter {
             ref mut shoot_timer,
             ref mut reload_timer,
             ref mut in_range,
-            bullets_per_shot,
+            ref pattern,
+            ref mut pattern_rotation,
+            ..
         } = enemy_type.as_mut()
         {
             let distance = transform.translation.distance(player_pos);
@@ -147,12 +615,20 @@ pub fn handle_shooter_enemies(
 
             if *in_range {
                 shoot_timer.tick(time.delta());
-                if shoot_timer.just_finished() {
+                // Hold fire until the player steps out from behind cover
+                if shoot_timer.finished()
+                    && has_line_of_sight(
+                        transform.translation.truncate(),
+                        player_pos.truncate(),
+                        obstacle_query.iter(),
+                    )
+                {
                     spawn_enemy_bullets(
                         &mut commands,
                         transform.translation,
                         player_pos,
-                        *bullets_per_shot,
+                        pattern,
+                        pattern_rotation,
                         &handle,
                     );
                     *shoot_timer = Timer::from_seconds(0.5, TimerMode::Once);
@@ -172,18 +648,14 @@ fn spawn_enemy_bullets(
     commands: &mut Commands,
     enemy_pos: Vec3,
     player_pos: Vec3,
-    num_bullets: usize,
+    pattern: &BulletPattern,
+    pattern_rotation: &mut f32,
     handle: &Res<GlobalTextureAtlas>,
 ) {
-    let direction = (player_pos - enemy_pos).normalize();
-    for _ in 0..num_bullets {
-        let spread = Vec3::new(
-            rand::random::<f32>() * 0.2 - 0.1,
-            rand::random::<f32>() * 0.2 - 0.1,
-            0.0,
-        );
-        let bullet_direction = direction + spread;
-        commands.spawn((
+    let aim = (player_pos - enemy_pos).truncate().normalize_or_zero();
+    for bullet in pattern.emit(aim, pattern_rotation) {
+        let delay = bullet.delay.unwrap_or(0.0);
+        let mut bullet_commands = commands.spawn((
             SpriteBundle {
                 texture: handle.image.clone().unwrap(),
                 transform: Transform::from_translation(enemy_pos)
@@ -195,52 +667,91 @@ fn spawn_enemy_bullets(
                 index: 16,
             },
             EnemyBullet,
-            BulletDirection(bullet_direction),
+            BulletDirection(bullet.direction.extend(0.0)),
             BulletStats {
-                speed: 200,
-                damage: 10,
-                lifespan: 2.0,
+                speed: ENEMY_BULLET_SPEED,
+                damage: ENEMY_BULLET_DAMAGE,
+                lifespan: ENEMY_BULLET_TIME_SECS,
             },
+            Collider::circle(BULLET_COLLIDER_RADIUS, CollisionLayer::EnemyBullet),
             InGameEntity,
-            HasLifespan::new(Duration::from_secs(2)),
+            HasLifespan::new(Duration::from_secs_f32(ENEMY_BULLET_TIME_SECS + delay)),
         ));
+        if let Some(delay) = bullet.delay {
+            bullet_commands.insert(BulletDelay(Timer::from_seconds(delay, TimerMode::Once)));
+        }
+        if let Some((rate, max_speed)) = bullet.acceleration {
+            bullet_commands.insert(BulletAcceleration {
+                rate,
+                max_speed,
+                current_speed: ENEMY_BULLET_SPEED as f32,
+            });
+        }
     }
 }
 
+#[allow(clippy::type_complexity)]
 pub fn update_enemy_bullets(
-    mut bullet_query: Query<(&mut Transform, &BulletDirection, &BulletStats), With<EnemyBullet>>,
+    mut bullet_query: Query<
+        (
+            &mut Transform,
+            &BulletDirection,
+            &BulletStats,
+            Option<&mut BulletDelay>,
+            Option<&mut BulletAcceleration>,
+        ),
+        With<EnemyBullet>,
+    >,
     time: Res<Time>,
 ) {
-    for (mut transform, direction, stats) in bullet_query.iter_mut() {
-        transform.translation += direction.0 * stats.speed as f32 * time.delta_seconds();
+    for (mut transform, direction, stats, delay, acceleration) in bullet_query.iter_mut() {
+        if let Some(mut delay) = delay {
+            delay.0.tick(time.delta());
+            if !delay.0.finished() {
+                continue;
+            }
+        }
+
+        let speed = match acceleration {
+            Some(mut acceleration) => {
+                acceleration.current_speed = (acceleration.current_speed
+                    + acceleration.rate * time.delta_seconds())
+                .min(acceleration.max_speed);
+                acceleration.current_speed
+            }
+            None => stats.speed as f32,
+        };
+        transform.translation += direction.0 * speed * time.delta_seconds();
     }
 }
 
+#[allow(clippy::type_complexity)]
 pub fn handle_enemy_bullet_collision(
     mut commands: Commands,
-    bullet_query: Query<(Entity, &Transform), With<EnemyBullet>>,
-    player_query: Query<(Entity, &Transform), (With<Player>, Without<InvincibilityEffect>)>,
+    bullet_query: Query<&BulletStats, With<EnemyBullet>>,
+    player_query: Query<Entity, (With<Player>, Without<InvincibilityEffect>)>,
+    mut ev_hit: EventReader<BulletHitPlayer>,
     mut ev_player_damaged: EventWriter<PlayerDamagedEvent>,
 ) {
-    if player_query.is_empty() {
+    let Ok(player_entity) = player_query.get_single() else {
+        ev_hit.clear();
         return;
-    }
-    let (player_entity, player_transform) = player_query.single();
+    };
 
-    for (bullet_entity, bullet_transform) in bullet_query.iter() {
-        if player_transform
-            .translation
-            .distance(bullet_transform.translation)
-            < 30.0
-        {
-            ev_player_damaged.send(PlayerDamagedEvent { damage: 10 });
+    for hit in ev_hit.read() {
+        let bullet_entity = hit.bullet;
+        let Ok(stats) = bullet_query.get(bullet_entity) else {
+            continue;
+        };
+        ev_player_damaged.send(PlayerDamagedEvent {
+            damage: stats.damage,
+        });
 
-            commands.entity(player_entity).insert(InvincibilityEffect(
-                Stopwatch::new(),
-                PLAYER_INVINCIBLE_TIME,
-            ));
+        commands.entity(player_entity).insert(InvincibilityEffect(
+            Stopwatch::new(),
+            PLAYER_INVINCIBLE_TIME,
+        ));
 
-            commands.entity(bullet_entity).despawn();
-        }
+        commands.entity(bullet_entity).despawn();
     }
 }
@@ -1,4 +1,5 @@
-use crate::enemy::Trail;
+use crate::collision::{Collider, CollisionLayer};
+use crate::enemy::{BulletPattern, Trail};
 use crate::world::InGameEntity;
 use bevy::prelude::*;
 use rand::Rng;
@@ -19,14 +20,89 @@ pub enum EnemyType {
         target_position: Option<Vec2>,
     },
     Shooter {
+        kind: ShooterKind,
         shoot_timer: Timer,
-        bullets_per_shot: usize,
+        pattern: BulletPattern,
+        pattern_rotation: f32,
         reload_timer: Timer,
         in_range: bool,
     },
+    Splitter {
+        generation: u32,
+    },
+    Summoner {
+        summon_timer: Timer,
+        minions_per_summon: u32,
+    },
+    Healer {
+        heal_timer: Timer,
+        heal_amount: u32,
+        heal_radius: f32,
+    },
+    Teleporter {
+        state: TeleportState,
+        teleport_timer: Timer,
+        target_position: Option<Vec2>,
+    },
+}
+
+/// Shooter definitions, each firing its own bullet pattern.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ShooterKind {
+    /// Accelerating spread aimed at the player.
+    Gunner,
+    /// Ring of bullets in every direction.
+    Ringer,
+    /// Rotating spiral arms.
+    Spinner,
+    /// Aimed fan with a fixed angle between bullets.
+    Fanner,
+    /// Aimed fan followed by a delayed ring.
+    Mortar,
 }
 
-#[derive(Clone, Debug)]
+impl ShooterKind {
+    pub fn random() -> Self {
+        match rand::thread_rng().gen_range(0..5) {
+            0 => ShooterKind::Gunner,
+            1 => ShooterKind::Ringer,
+            2 => ShooterKind::Spinner,
+            3 => ShooterKind::Fanner,
+            _ => ShooterKind::Mortar,
+        }
+    }
+
+    pub fn pattern(self) -> BulletPattern {
+        match self {
+            ShooterKind::Gunner => BulletPattern::Accelerating {
+                rate: 300.0,
+                max_speed: 500.0,
+                pattern: Box::new(BulletPattern::Spread {
+                    count: 4,
+                    spread: 0.1,
+                }),
+            },
+            ShooterKind::Ringer => BulletPattern::Radial { count: 12 },
+            ShooterKind::Spinner => BulletPattern::Spiral { arms: 3, step: 0.3 },
+            ShooterKind::Fanner => BulletPattern::Fan {
+                count: 5,
+                angle: 0.25,
+            },
+            ShooterKind::Mortar => BulletPattern::Composite(vec![
+                BulletPattern::Fan {
+                    count: 3,
+                    angle: 0.2,
+                },
+                BulletPattern::Delayed {
+                    delay: 0.6,
+                    pattern: Box::new(BulletPattern::Radial { count: 8 }),
+                },
+            ]),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
 pub enum ChargeState {
     Approaching,
     Preparing,
@@ -34,24 +110,29 @@ pub enum ChargeState {
     CoolingDown,
 }
 
+#[derive(Clone, Debug, PartialEq)]
+pub enum TeleportState {
+    Chasing,
+    Telegraphing,
+    Recovering,
+}
+
 pub struct EnemyConfig {
     pub health: u32,
     pub speed: u32,
     pub damage: u32,
     pub sprite_index: usize,
     pub xp: u32,
+    pub color: Color,
+    pub scale: f32,
+    pub mass: f32,
 }
 
 impl EnemyType {
     pub fn random() -> Self {
         let mut rng = rand::thread_rng();
-        match rng.gen_range(0..4) {
-            0 => EnemyType::Shooter {
-                shoot_timer: Timer::from_seconds(0.5, TimerMode::Once),
-                bullets_per_shot: rng.gen_range(3..=6),
-                reload_timer: Timer::from_seconds(2.0, TimerMode::Once),
-                in_range: false,
-            },
+        match rng.gen_range(0..8) {
+            0 => EnemyType::shooter(ShooterKind::random()),
             1 => EnemyType::LeaveTrail {
                 timer: Timer::from_seconds(0.1, TimerMode::Repeating),
                 trail_damage: 4,
@@ -63,10 +144,36 @@ impl EnemyType {
                 charge_speed: 15,
                 target_position: None,
             },
+            3 => EnemyType::Splitter { generation: 0 },
+            4 => EnemyType::Summoner {
+                summon_timer: Timer::from_seconds(4.0, TimerMode::Repeating),
+                minions_per_summon: 2,
+            },
+            5 => EnemyType::Healer {
+                heal_timer: Timer::from_seconds(2.0, TimerMode::Repeating),
+                heal_amount: 15,
+                heal_radius: 150.0,
+            },
+            6 => EnemyType::Teleporter {
+                state: TeleportState::Chasing,
+                teleport_timer: Timer::from_seconds(3.0, TimerMode::Once),
+                target_position: None,
+            },
             _ => EnemyType::Basic,
         }
     }
 
+    pub fn shooter(kind: ShooterKind) -> Self {
+        EnemyType::Shooter {
+            kind,
+            shoot_timer: Timer::from_seconds(0.5, TimerMode::Once),
+            pattern: kind.pattern(),
+            pattern_rotation: 0.0,
+            reload_timer: Timer::from_seconds(2.0, TimerMode::Once),
+            in_range: false,
+        }
+    }
+
     pub fn get_config(&self) -> EnemyConfig {
         match self {
             EnemyType::Basic => EnemyConfig {
@@ -75,6 +182,9 @@ impl EnemyType {
                 damage: 6,
                 sprite_index: 8,
                 xp: 4,
+                color: Color::WHITE,
+                scale: 1.0,
+                mass: 1.0,
             },
             EnemyType::LeaveTrail { .. } => EnemyConfig {
                 health: 50,
@@ -82,6 +192,9 @@ impl EnemyType {
                 damage: 6,
                 sprite_index: 12,
                 xp: 5,
+                color: Color::WHITE,
+                scale: 1.0,
+                mass: 0.8,
             },
             EnemyType::Charge { .. } => EnemyConfig {
                 health: 80,
@@ -89,6 +202,9 @@ impl EnemyType {
                 damage: 8,
                 sprite_index: 20,
                 xp: 8,
+                color: Color::WHITE,
+                scale: 1.0,
+                mass: 2.0,
             },
             EnemyType::Shooter { .. } => EnemyConfig {
                 health: 100,
@@ -96,6 +212,49 @@ impl EnemyType {
                 damage: 0,
                 sprite_index: 28,
                 xp: 10,
+                color: Color::WHITE,
+                scale: 1.0,
+                mass: 1.0,
+            },
+            EnemyType::Splitter { generation } => EnemyConfig {
+                health: 120 >> generation,
+                speed: 7 + generation * 2,
+                damage: 6,
+                sprite_index: 32,
+                xp: 6,
+                color: Color::WHITE,
+                scale: 1.0 - *generation as f32 * 0.25,
+                mass: 1.0 - *generation as f32 * 0.3,
+            },
+            EnemyType::Summoner { .. } => EnemyConfig {
+                health: 120,
+                speed: 5,
+                damage: 0,
+                sprite_index: 36,
+                xp: 12,
+                color: Color::WHITE,
+                scale: 1.1,
+                mass: 1.5,
+            },
+            EnemyType::Healer { .. } => EnemyConfig {
+                health: 80,
+                speed: 6,
+                damage: 4,
+                sprite_index: 40,
+                xp: 10,
+                color: Color::WHITE,
+                scale: 1.0,
+                mass: 1.0,
+            },
+            EnemyType::Teleporter { .. } => EnemyConfig {
+                health: 70,
+                speed: 5,
+                damage: 10,
+                sprite_index: 44,
+                xp: 10,
+                color: Color::WHITE,
+                scale: 1.0,
+                mass: 0.8,
             },
         }
     }
@@ -159,9 +318,32 @@ impl EnemyType {
                     }
                 }
             }
+            EnemyType::Splitter { .. } => {
+                (player_pos - current_pos).normalize() * base_speed as f32
+            }
+            EnemyType::Summoner { .. } => {
+                let distance = current_pos.distance(player_pos);
+                let keep_range = 400.0;
+
+                if distance > keep_range + 50.0 {
+                    (player_pos - current_pos).normalize() * base_speed as f32
+                } else if distance < keep_range - 50.0 {
+                    (current_pos - player_pos).normalize() * base_speed as f32
+                } else {
+                    Vec3::ZERO
+                }
+            }
+            EnemyType::Healer { .. } => (player_pos - current_pos).normalize() * base_speed as f32,
+            EnemyType::Teleporter { state, .. } => {
+                if *state == TeleportState::Chasing {
+                    (player_pos - current_pos).normalize() * base_speed as f32
+                } else {
+                    Vec3::ZERO
+                }
+            }
             EnemyType::Shooter { in_range, .. } => {
                 let distance = current_pos.distance(player_pos);
-                let max_range = 300.0; 
+                let max_range = 300.0;
                 *in_range = distance <= max_range;
 
                 if distance > max_range + 50.0 {
@@ -188,19 +370,20 @@ impl EnemyType {
         }
     }
 
-    pub fn update_shooter(&mut self, delta: Duration) -> Option<usize> {
+    pub fn update_shooter(&mut self, delta: Duration) -> Option<&BulletPattern> {
         if let EnemyType::Shooter {
             shoot_timer,
-            bullets_per_shot,
+            pattern,
             reload_timer,
             in_range,
+            ..
         } = self
         {
             if *in_range {
                 shoot_timer.tick(delta);
                 if shoot_timer.just_finished() {
                     *shoot_timer = Timer::from_seconds(0.1, TimerMode::Once);
-                    return Some(*bullets_per_shot);
+                    return Some(pattern);
                 }
             } else {
                 reload_timer.tick(delta);
@@ -218,16 +401,14 @@ fn spawn_trail(commands: &mut Commands, position: Vec3, damage: u32) {
         SpriteBundle {
             sprite: Sprite {
                 color: Color::srgba(0.0, 0.8, 0.0, 0.5),
-                custom_size: Some(Vec2::new(20.0, 20.0)),
+                custom_size: Some(Vec2::splat(crate::TRAIL_HALF_SIZE * 2.0)),
                 ..default()
             },
             transform: Transform::from_translation(position),
             ..default()
         },
-        Trail {
-            damage,
-            radius: 10.0,
-        },
+        Trail { damage },
+        Collider::aabb(Vec2::splat(crate::TRAIL_HALF_SIZE), CollisionLayer::Hazard),
         crate::gun::HasLifespan::new(Duration::from_secs_f32(5.0)),
         InGameEntity,
     ));
@@ -2,12 +2,15 @@ use bevy::diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin};
 use bevy::prelude::*;
 
 use crate::armor::{Armor, ArmorStats};
+use crate::effects::{ActiveEffects, EffectTimer};
 use crate::enemy::Enemy;
-use crate::player::{Defense, Health, Player, PlayerInventory};
+use crate::inventory::PlayerInventory;
+use crate::player::{Defense, Health, Player};
 use crate::resources::{Level, Wave};
 use crate::state::GameState;
+use crate::wallet::Wallet;
 use crate::world::InGameEntity;
-use crate::UiFont;
+use crate::{Settings, UiFont, EFFECT_ICON_SIZE};
 
 pub struct GuiPlugin;
 
@@ -16,7 +19,17 @@ struct DebugText;
 #[derive(Component)]
 struct PotionDisplay;
 #[derive(Component)]
+struct ArmorWarning;
+#[derive(Component)]
 struct MainMenuItem;
+#[derive(Component)]
+struct EffectDisplay;
+#[derive(Component)]
+struct EffectIcon(usize);
+#[derive(Component)]
+struct EffectTimerFill(usize);
+#[derive(Component)]
+struct EffectTimerText(usize);
 
 impl Plugin for GuiPlugin {
     fn build(&self, app: &mut App) {
@@ -29,15 +42,30 @@ impl Plugin for GuiPlugin {
             )
             .add_systems(
                 OnEnter(GameState::GameInit),
-                (spawn_debug_text, setup_potion_display),
+                (
+                    spawn_debug_text,
+                    setup_potion_display,
+                    setup_armor_warning,
+                    setup_effect_display,
+                ),
             )
             .add_systems(
                 Update,
-                (update_debug_text, update_potion_display).run_if(in_state(GameState::InGame)),
+                (
+                    update_debug_text,
+                    update_potion_display,
+                    update_armor_warning,
+                    update_effect_display,
+                )
+                    .run_if(in_state(GameState::InGame)),
             )
             .add_systems(
                 Update,
-                (handle_pause_input, handle_game_restart)
+                (
+                    handle_pause_input,
+                    handle_game_restart,
+                    handle_settings_input,
+                )
                     .run_if(in_state(GameState::InGame).or_else(in_state(GameState::Paused))),
             );
     }
@@ -83,7 +111,6 @@ fn spawn_debug_text(mut commands: Commands, font: Res<UiFont>) {
                                 font: font.0.clone(),
                                 font_size: 40.0,
                                 color: Color::WHITE,
-                                ..default()
                             },
                         ),
                         DebugText,
@@ -140,7 +167,6 @@ fn setup_potion_display(mut commands: Commands, font: Res<UiFont>) {
                         font: font.0.clone(),
                         font_size: 30.0,
                         color: Color::WHITE,
-                        ..default()
                     },
                 ),
                 PotionDisplay,
@@ -152,32 +178,228 @@ fn update_potion_display(
     mut query: Query<&mut Text, With<PotionDisplay>>,
     player_query: Query<&PlayerInventory, With<Player>>,
     armor_query: Query<&ArmorStats, With<Armor>>,
+    wallet: Res<Wallet>,
 ) {
     let mut text = query.single_mut();
     let player_inventory = player_query.single();
-    let health_potions_count = player_inventory.health_potions.len();
-    let speed_potions_count = player_inventory.speed_potions.len();
 
-    let armor_info = if let Some(active_armor_entity) = player_inventory
-        .armors
-        .get(player_inventory.active_armor_index)
-    {
-        if let Ok(armor_stats) = armor_query.get(*active_armor_entity) {
-            format!(
-                "Armor Defense: {}, Durability: {}",
-                armor_stats.defense, armor_stats.durability
-            )
-        } else {
-            "No Armor".to_string()
-        }
-    } else {
+    let worn = player_inventory
+        .worn_armor()
+        .filter_map(|entity| armor_query.get(entity).ok())
+        .collect::<Vec<_>>();
+    let armor_info = if worn.is_empty() {
         "No Armor".to_string()
+    } else {
+        let durability = worn
+            .iter()
+            .map(|stats| {
+                format!(
+                    "{} {}/{}",
+                    stats.slot.name(),
+                    stats.durability,
+                    stats.max_durability
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!(
+            "Armor Defense: {}, Durability: {}",
+            worn.iter()
+                .filter(|stats| !stats.is_broken())
+                .map(|stats| stats.defense)
+                .sum::<u32>(),
+            durability
+        )
+    };
+
+    text.sections[0].value = format!("Coins: {}\n{}", wallet.coins, armor_info);
+}
+
+fn setup_armor_warning(mut commands: Commands, font: Res<UiFont>) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    top: Val::Px(60.0),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            InGameEntity,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: font.0.clone(),
+                        font_size: 28.0,
+                        color: Color::WHITE,
+                    },
+                )
+                .with_text_justify(JustifyText::Center),
+                ArmorWarning,
+            ));
+        });
+}
+
+/// Warns about worn pieces that are broken or close to it, in red once any
+/// of them has broken.
+fn update_armor_warning(
+    mut query: Query<&mut Text, With<ArmorWarning>>,
+    player_query: Query<&PlayerInventory, With<Player>>,
+    armor_query: Query<&ArmorStats, With<Armor>>,
+) {
+    let (Ok(mut text), Ok(inventory)) = (query.get_single_mut(), player_query.get_single()) else {
+        return;
+    };
+    let worn = inventory
+        .worn_armor()
+        .filter_map(|entity| armor_query.get(entity).ok())
+        .collect::<Vec<_>>();
+    let warnings = worn
+        .iter()
+        .filter_map(|stats| {
+            if stats.is_broken() {
+                Some(format!("{} armor broken!", stats.slot.name()))
+            } else if stats.is_worn_down() {
+                Some(format!("{} armor almost broken", stats.slot.name()))
+            } else {
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let section = &mut text.sections[0];
+    section.value = warnings.join("\n");
+    section.style.color = if worn.iter().any(|stats| stats.is_broken()) {
+        Color::srgb(1.0, 0.2, 0.2)
+    } else {
+        Color::srgb(1.0, 0.6, 0.1)
+    };
+}
+
+fn setup_effect_display(mut commands: Commands) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Px(10.0),
+                bottom: Val::Px(10.0),
+                column_gap: Val::Px(6.0),
+                ..default()
+            },
+            ..default()
+        },
+        EffectDisplay,
+        InGameEntity,
+    ));
+}
+
+/// One icon per timed effect on the player, darkened from the top as its
+/// timer runs out.
+fn update_effect_display(
+    mut commands: Commands,
+    font: Res<UiFont>,
+    display_query: Query<(Entity, Option<&Children>), With<EffectDisplay>>,
+    player_query: Query<&ActiveEffects, With<Player>>,
+    mut icon_query: Query<(&EffectIcon, &mut BackgroundColor)>,
+    mut fill_query: Query<(&EffectTimerFill, &mut Style)>,
+    mut text_query: Query<(&EffectTimerText, &mut Text)>,
+) {
+    let (Ok((display, children)), Ok(effects)) =
+        (display_query.get_single(), player_query.get_single())
+    else {
+        return;
     };
+    let timers = effects.timers().collect::<Vec<_>>();
+
+    if children.map_or(0, |children| children.len()) != timers.len() {
+        commands.entity(display).despawn_descendants();
+        commands.entity(display).with_children(|parent| {
+            for (index, timer) in timers.iter().enumerate() {
+                spawn_effect_icon(parent, &font.0, index, timer);
+            }
+        });
+        return;
+    }
+
+    for (icon, mut color) in icon_query.iter_mut() {
+        if let Some(timer) = timers.get(icon.0) {
+            *color = timer.source.tint().into();
+        }
+    }
+    for (fill, mut style) in fill_query.iter_mut() {
+        if let Some(timer) = timers.get(fill.0) {
+            style.height = Val::Percent((1.0 - timer.fraction_left) * 100.0);
+        }
+    }
+    for (label, mut text) in text_query.iter_mut() {
+        if let Some(timer) = timers.get(label.0) {
+            text.sections[0].value = effect_label(timer);
+        }
+    }
+}
+
+fn spawn_effect_icon(
+    parent: &mut ChildBuilder,
+    font: &Handle<Font>,
+    index: usize,
+    timer: &EffectTimer,
+) {
+    parent
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Px(EFFECT_ICON_SIZE),
+                    height: Val::Px(EFFECT_ICON_SIZE),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: timer.source.tint().into(),
+                ..default()
+            },
+            EffectIcon(index),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        top: Val::Px(0.0),
+                        width: Val::Percent(100.0),
+                        height: Val::Percent((1.0 - timer.fraction_left) * 100.0),
+                        ..default()
+                    },
+                    background_color: Color::BLACK.with_alpha(0.6).into(),
+                    ..default()
+                },
+                EffectTimerFill(index),
+            ));
+            parent.spawn((
+                TextBundle::from_section(
+                    effect_label(timer),
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 18.0,
+                        color: Color::WHITE,
+                    },
+                ),
+                EffectTimerText(index),
+            ));
+        });
+}
 
-    text.sections[0].value = format!(
-        "Health Potions: {}, Speed Potions: {}\n{}",
-        health_potions_count, speed_potions_count, armor_info
-    );
+fn effect_label(timer: &EffectTimer) -> String {
+    if timer.stacks > 1 {
+        format!("{:.0}x{}", timer.remaining.ceil(), timer.stacks)
+    } else {
+        format!("{:.0}", timer.remaining.ceil())
+    }
 }
 
 fn setup_main_menu(mut commands: Commands) {
@@ -225,11 +447,8 @@ fn handle_main_menu_buttons(
     mut next_state: ResMut<NextState<GameState>>,
 ) {
     for interaction in interaction_query.iter() {
-        match interaction {
-            Interaction::Pressed => {
-                next_state.set(GameState::GameInit);
-            }
-            _ => {}
+        if *interaction == Interaction::Pressed {
+            next_state.set(GameState::GameInit);
         }
     }
 }
@@ -266,3 +485,15 @@ fn handle_game_restart(
         next_state.set(GameState::GameInit);
     }
 }
+
+fn handle_settings_input(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut settings: ResMut<Settings>,
+) {
+    if keyboard_input.just_pressed(KeyCode::KeyH) {
+        settings.show_enemy_health_bars = !settings.show_enemy_health_bars;
+    }
+    if keyboard_input.just_pressed(KeyCode::KeyN) {
+        settings.show_damage_numbers = !settings.show_damage_numbers;
+    }
+}
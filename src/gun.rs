@@ -4,10 +4,15 @@ use std::f32::consts::PI;
 use bevy::math::{vec2, vec3};
 use bevy::prelude::*;
 use bevy::time::Stopwatch;
-use player::{handle_player_input, PlayerInventory};
+use inventory::{EquipmentSlot, InventoryAction, Item, ItemCategory, PlayerInventory, SlotId};
+use player::handle_player_input;
 use rand::Rng;
+use serde::Deserialize;
 use world::InGameEntity;
 
+use crate::collision::{Collider, CollisionLayer};
+use crate::effects::{ActiveEffects, Stat};
+use crate::enemy::StatusEffect;
 use crate::player::Player;
 use crate::state::GameState;
 use crate::*;
@@ -30,20 +35,80 @@ pub enum GunType {
 #[derive(Component)]
 pub struct Bullet;
 
-#[derive(Component)]
+#[derive(Component, Clone)]
 pub struct BulletStats {
     pub speed: u32,
     pub damage: u32,
     pub lifespan: f32,
 }
 
-#[derive(Component)]
+#[derive(Component, Clone)]
 pub struct GunStats {
     pub bullets_per_shot: usize,
     pub firing_interval: f32,
     pub bullet_spread: f32,
 }
 
+/// Numbers derived from a gun's stats, used to compare weapons.
+#[derive(Clone, Copy)]
+pub struct WeaponSummary {
+    pub dps: f32,
+    pub range: f32,
+    pub spread_degrees: f32,
+}
+
+impl WeaponSummary {
+    pub fn new(gun_stats: &GunStats, bullet_stats: &BulletStats) -> Self {
+        Self {
+            dps: gun_stats.bullets_per_shot as f32 * bullet_stats.damage as f32
+                / gun_stats.firing_interval,
+            range: bullet_stats.speed as f32 * bullet_stats.lifespan,
+            // The spread is added to each axis of the unit aim direction
+            spread_degrees: 2.0 * gun_stats.bullet_spread.atan().to_degrees(),
+        }
+    }
+}
+
+/// A part fitted to a gun at the workbench. Each kind fits a gun once.
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GunAttachment {
+    Scope,
+    LongBarrel,
+    DrumMagazine,
+    HollowPoints,
+}
+
+/// What has been fitted to a gun so far.
+#[derive(Component, Clone, Default)]
+pub struct Attachments(pub Vec<GunAttachment>);
+
+impl GunAttachment {
+    pub fn name(&self) -> &'static str {
+        match self {
+            GunAttachment::Scope => "Scope",
+            GunAttachment::LongBarrel => "Long Barrel",
+            GunAttachment::DrumMagazine => "Drum Magazine",
+            GunAttachment::HollowPoints => "Hollow Points",
+        }
+    }
+
+    pub fn apply(&self, gun_stats: &mut GunStats, bullet_stats: &mut BulletStats) {
+        match self {
+            GunAttachment::Scope => gun_stats.bullet_spread *= 0.6,
+            GunAttachment::LongBarrel => {
+                bullet_stats.speed = (bullet_stats.speed as f32 * 1.25).round() as u32;
+            }
+            GunAttachment::DrumMagazine => gun_stats.firing_interval *= 0.85,
+            GunAttachment::HollowPoints => {
+                bullet_stats.damage = (bullet_stats.damage as f32 * 1.2).round() as u32;
+            }
+        }
+    }
+}
+
+#[derive(Component, Clone, Copy)]
+pub struct OnHitEffect(pub StatusEffect);
+
 #[derive(Component)]
 pub struct HasLifespan {
     pub spawn_time: Instant,
@@ -65,10 +130,12 @@ pub struct BulletDirection(pub Vec3);
 #[derive(Bundle)]
 pub struct GunBundle {
     pub gun: Gun,
+    pub item: Item,
     pub gun_timer: GunTimer,
     pub gun_type: GunType,
     pub bullet_stats: BulletStats,
     pub gun_stats: GunStats,
+    pub attachments: Attachments,
     pub in_game_entity: InGameEntity,
     pub sprite_bundle: SpriteBundle,
 }
@@ -77,6 +144,7 @@ impl Default for GunBundle {
     fn default() -> Self {
         Self {
             gun: Gun,
+            item: Item::weapon("Pistol"),
             gun_timer: GunTimer(Stopwatch::new()),
             gun_type: GunType::Default,
             bullet_stats: BulletStats {
@@ -89,6 +157,7 @@ impl Default for GunBundle {
                 firing_interval: BULLET_SPAWN_INTERVAL,
                 bullet_spread: BULLET_SPREAD,
             },
+            attachments: Attachments::default(),
             in_game_entity: InGameEntity,
             sprite_bundle: Default::default(),
         }
@@ -100,13 +169,18 @@ impl Plugin for GunPlugin {
         app.add_systems(
             Update,
             (
-                update_gun_transform.after(handle_player_input),
                 update_bullets,
                 handle_gun_firing,
                 despawn_entities_reach_lifespan,
                 switch_gun,
             )
                 .run_if(in_state(GameState::InGame)),
+        )
+        .add_systems(
+            Update,
+            update_gun_transform
+                .after(handle_player_input)
+                .run_if(in_state(GameState::InGame).or_else(in_state(GameState::Town))),
         );
     }
 }
@@ -130,8 +204,8 @@ fn update_gun_transform(
     };
 
     // Retrieve the active gun from the player's inventory
-    if let Some(active_gun_entity) = gun_inventory.guns.get(gun_inventory.active_gun_index) {
-        if let Ok(mut gun_transform) = gun_query.get_mut(*active_gun_entity) {
+    if let Some(active_gun_entity) = gun_inventory.weapon {
+        if let Ok(mut gun_transform) = gun_query.get_mut(active_gun_entity) {
             let angle = (player_pos.y - cursor_pos.y).atan2(player_pos.x - cursor_pos.x) + PI;
             gun_transform.rotation = Quat::from_rotation_z(angle);
 
@@ -163,20 +237,38 @@ fn despawn_entities_reach_lifespan(
     }
 }
 
+#[allow(clippy::type_complexity)]
 fn handle_gun_firing(
     mut commands: Commands,
     time: Res<Time>,
-    player_query: Query<&PlayerInventory, With<Player>>,
-    mut gun_query: Query<(&Transform, &mut GunTimer, &GunType, &BulletStats, &GunStats), With<Gun>>,
+    player_query: Query<(&PlayerInventory, Option<&ActiveEffects>), With<Player>>,
+    mut gun_query: Query<
+        (
+            &Transform,
+            &mut GunTimer,
+            &GunType,
+            &BulletStats,
+            &GunStats,
+            Option<&OnHitEffect>,
+        ),
+        With<Gun>,
+    >,
     handle: Res<GlobalTextureAtlas>,
 ) {
-    if let Ok(inventory) = player_query.get_single() {
-        if let Ok((gun_transform, mut gun_timer, gun_type, bullet_stats, gun_stats)) =
-            gun_query.get_mut(inventory.guns[inventory.active_gun_index])
+    let Ok((inventory, effects)) = player_query.get_single() else {
+        return;
+    };
+    // Buffs like fury speed up firing and raise damage
+    let scale = |stat: Stat, value: f32| effects.map_or(value, |e| e.scale(stat, value));
+    if let Some(weapon) = inventory.weapon {
+        if let Ok((gun_transform, mut gun_timer, gun_type, bullet_stats, gun_stats, on_hit)) =
+            gun_query.get_mut(weapon)
         {
             gun_timer.0.tick(time.delta());
+            let damage = scale(Stat::Damage, bullet_stats.damage as f32).round() as u32;
 
-            if gun_timer.0.elapsed_secs() >= gun_stats.firing_interval {
+            if gun_timer.0.elapsed_secs() >= gun_stats.firing_interval / scale(Stat::FireRate, 1.0)
+            {
                 gun_timer.0.reset();
                 let gun_pos = gun_transform.translation.truncate();
                 let mut rng = rand::thread_rng();
@@ -195,7 +287,7 @@ fn handle_gun_firing(
                                     ),
                                 bullet_direction.z,
                             );
-                            commands.spawn((
+                            let mut bullet = commands.spawn((
                                 SpriteBundle {
                                     texture: handle.image.clone().unwrap(),
                                     transform: Transform::from_translation(vec3(
@@ -212,13 +304,20 @@ fn handle_gun_firing(
                                 BulletDirection(dir),
                                 BulletStats {
                                     speed: bullet_stats.speed,
-                                    damage: bullet_stats.damage,
+                                    damage,
                                     lifespan: bullet_stats.lifespan,
                                 },
                                 gun_type.clone(),
+                                Collider::circle(
+                                    BULLET_COLLIDER_RADIUS,
+                                    CollisionLayer::PlayerBullet,
+                                ),
                                 InGameEntity,
                                 HasLifespan::new(Duration::from_secs_f32(bullet_stats.lifespan)),
                             ));
+                            if let Some(on_hit) = on_hit {
+                                bullet.insert(*on_hit);
+                            }
                         }
                     }
                     GunType::Gun1 => {
@@ -234,7 +333,7 @@ fn handle_gun_firing(
                                     ),
                                 bullet_direction.z,
                             );
-                            commands.spawn((
+                            let mut bullet = commands.spawn((
                                 SpriteBundle {
                                     texture: handle.image.clone().unwrap(),
                                     transform: Transform::from_translation(vec3(
@@ -251,13 +350,20 @@ fn handle_gun_firing(
                                 BulletDirection(dir),
                                 BulletStats {
                                     speed: bullet_stats.speed,
-                                    damage: bullet_stats.damage,
+                                    damage,
                                     lifespan: bullet_stats.lifespan,
                                 },
                                 gun_type.clone(),
+                                Collider::circle(
+                                    BULLET_COLLIDER_RADIUS,
+                                    CollisionLayer::PlayerBullet,
+                                ),
                                 InGameEntity,
                                 HasLifespan::new(Duration::from_secs_f32(bullet_stats.lifespan)),
                             ));
+                            if let Some(on_hit) = on_hit {
+                                bullet.insert(*on_hit);
+                            }
                         }
                     }
                     GunType::Gun2 => todo!(),
@@ -267,26 +373,40 @@ fn handle_gun_firing(
     }
 }
 
+#[allow(clippy::type_complexity)]
 fn switch_gun(
-    mut player_query: Query<(&mut PlayerInventory, &Transform), With<Player>>,
+    player_query: Query<(&PlayerInventory, &Transform), With<Player>>,
     keyboard_input: Res<ButtonInput<KeyCode>>,
     mut gun_query: Query<(&mut Transform, &mut Visibility), (With<Gun>, Without<Player>)>,
+    item_query: Query<&Item>,
+    mut ev_inventory_action: EventWriter<InventoryAction>,
+    mut cursor: Local<usize>,
 ) {
     if player_query.is_empty() {
         return;
     }
 
-    let (mut inventory, player_transform) = player_query.single_mut();
+    let (inventory, player_transform) = player_query.single();
 
     if keyboard_input.just_pressed(KeyCode::KeyE) {
-        // Cycle to the next gun in the inventory
-        inventory.active_gun_index = (inventory.active_gun_index + 1) % inventory.guns.len();
+        // Swap the next gun in the bag into the weapon slot
+        if let Some(index) = inventory.next_in_bag(*cursor, |entity| {
+            item_query
+                .get(entity)
+                .is_ok_and(|item| item.category == ItemCategory::Weapon)
+        }) {
+            *cursor = index;
+            ev_inventory_action.send(InventoryAction::Swap {
+                a: SlotId::Equipment(EquipmentSlot::Weapon),
+                b: SlotId::Bag(index),
+            });
+        }
     }
 
-    // Update the visibility of all guns and the position of the active gun
-    for (gun_index, gun_entity) in inventory.guns.iter().enumerate() {
-        if let Ok((mut gun_transform, mut gun_visibility)) = gun_query.get_mut(*gun_entity) {
-            if gun_index == inventory.active_gun_index {
+    // Update the visibility of all carried guns and the position of the active gun
+    for (_, gun_entity) in inventory.slots() {
+        if let Ok((mut gun_transform, mut gun_visibility)) = gun_query.get_mut(gun_entity) {
+            if Some(gun_entity) == inventory.weapon {
                 // Active gun
                 gun_transform.translation = player_transform.translation;
                 *gun_visibility = Visibility::Visible;
@@ -0,0 +1,259 @@
+use bevy::prelude::*;
+
+use crate::inventory::{EquipmentSlot, Item, PlayerInventory, SlotId};
+use crate::player::Player;
+use crate::state::GameState;
+use crate::world::InGameEntity;
+use crate::*;
+
+pub struct HotbarPlugin;
+
+/// Keys for the quick-use slots, in slot order.
+pub const HOTBAR_KEYS: [KeyCode; 9] = [
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+    KeyCode::Digit8,
+    KeyCode::Digit9,
+];
+
+const _: () = assert!(QUICK_USE_SLOTS <= HOTBAR_KEYS.len());
+
+/// One cooldown per quick-use slot, started whenever the slot is used.
+#[derive(Component)]
+pub struct HotbarCooldowns([Timer; QUICK_USE_SLOTS]);
+
+/// Sent when the player uses the item in a quick-use slot. What happens
+/// depends on the item; potions are handled in `potion.rs`.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct HotbarUseEvent {
+    pub slot: usize,
+    pub item: Entity,
+}
+
+#[derive(Component)]
+struct HotbarSlotIcon(usize);
+#[derive(Component)]
+struct HotbarSlotCount(usize);
+#[derive(Component)]
+struct HotbarCooldownFill(usize);
+
+impl Default for HotbarCooldowns {
+    fn default() -> Self {
+        Self(std::array::from_fn(|_| {
+            let mut timer = Timer::from_seconds(QUICK_USE_COOLDOWN, TimerMode::Once);
+            timer.tick(timer.duration());
+            timer
+        }))
+    }
+}
+
+impl HotbarCooldowns {
+    pub fn ready(&self, slot: usize) -> bool {
+        self.0.get(slot).is_some_and(Timer::finished)
+    }
+
+    /// How much of the cooldown is left, from 1 right after use to 0.
+    pub fn fraction_left(&self, slot: usize) -> f32 {
+        self.0.get(slot).map_or(0.0, Timer::fraction_remaining)
+    }
+
+    fn start(&mut self, slot: usize) {
+        if let Some(timer) = self.0.get_mut(slot) {
+            timer.reset();
+        }
+    }
+}
+
+impl Plugin for HotbarPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<HotbarUseEvent>()
+            .add_systems(OnEnter(GameState::GameInit), setup_hotbar_display)
+            .add_systems(
+                Update,
+                (tick_hotbar_cooldowns, handle_hotbar_input)
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
+            )
+            .add_systems(
+                Update,
+                update_hotbar_display
+                    .run_if(in_state(GameState::InGame).or_else(in_state(GameState::Paused))),
+            );
+    }
+}
+
+pub fn hotbar_slot(index: usize) -> SlotId {
+    SlotId::Equipment(EquipmentSlot::QuickUse(index))
+}
+
+fn tick_hotbar_cooldowns(time: Res<Time>, mut query: Query<&mut HotbarCooldowns>) {
+    for mut cooldowns in query.iter_mut() {
+        for timer in cooldowns.0.iter_mut() {
+            timer.tick(time.delta());
+        }
+    }
+}
+
+pub fn handle_hotbar_input(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut player_query: Query<(&PlayerInventory, &mut HotbarCooldowns), With<Player>>,
+    mut ev_use: EventWriter<HotbarUseEvent>,
+) {
+    let Ok((inventory, mut cooldowns)) = player_query.get_single_mut() else {
+        return;
+    };
+    for (slot, key) in HOTBAR_KEYS.iter().take(QUICK_USE_SLOTS).enumerate() {
+        if !keyboard_input.just_pressed(*key) || !cooldowns.ready(slot) {
+            continue;
+        }
+        if let Some(item) = inventory.get(hotbar_slot(slot)) {
+            cooldowns.start(slot);
+            ev_use.send(HotbarUseEvent { slot, item });
+        }
+    }
+}
+
+fn setup_hotbar_display(
+    mut commands: Commands,
+    handle: Res<GlobalTextureAtlas>,
+    font: Res<UiFont>,
+) {
+    let text_style = |font_size: f32| TextStyle {
+        font: font.0.clone(),
+        font_size,
+        color: Color::WHITE,
+    };
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    bottom: Val::Px(10.0),
+                    justify_content: JustifyContent::Center,
+                    column_gap: Val::Px(6.0),
+                    ..default()
+                },
+                ..default()
+            },
+            InGameEntity,
+        ))
+        .with_children(|parent| {
+            for index in 0..QUICK_USE_SLOTS {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            width: Val::Px(HOTBAR_SLOT_SIZE),
+                            height: Val::Px(HOTBAR_SLOT_SIZE),
+                            border: UiRect::all(Val::Px(2.0)),
+                            ..default()
+                        },
+                        background_color: Color::srgba(0.1, 0.1, 0.1, 0.8).into(),
+                        border_color: BorderColor(Color::BLACK),
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn((
+                            ImageBundle {
+                                style: Style {
+                                    width: Val::Percent(100.0),
+                                    height: Val::Percent(100.0),
+                                    ..default()
+                                },
+                                image: UiImage::new(handle.image.clone().unwrap_or_default()),
+                                visibility: Visibility::Hidden,
+                                ..default()
+                            },
+                            TextureAtlas {
+                                layout: handle.layout.clone().unwrap_or_default(),
+                                index: 0,
+                            },
+                            HotbarSlotIcon(index),
+                        ));
+                        parent.spawn((
+                            NodeBundle {
+                                style: Style {
+                                    position_type: PositionType::Absolute,
+                                    top: Val::Px(0.0),
+                                    width: Val::Percent(100.0),
+                                    height: Val::Percent(0.0),
+                                    ..default()
+                                },
+                                background_color: Color::BLACK.with_alpha(0.6).into(),
+                                ..default()
+                            },
+                            HotbarCooldownFill(index),
+                        ));
+                        parent.spawn(
+                            TextBundle::from_section((index + 1).to_string(), text_style(16.0))
+                                .with_style(Style {
+                                    position_type: PositionType::Absolute,
+                                    left: Val::Px(2.0),
+                                    top: Val::Px(0.0),
+                                    ..default()
+                                }),
+                        );
+                        parent.spawn((
+                            TextBundle::from_section("", text_style(18.0)).with_style(Style {
+                                position_type: PositionType::Absolute,
+                                right: Val::Px(2.0),
+                                bottom: Val::Px(0.0),
+                                ..default()
+                            }),
+                            HotbarSlotCount(index),
+                        ));
+                    });
+            }
+        });
+}
+
+/// Shows each slot's item tinted like its sprite, the stack size, and a
+/// shade that draws back to the top as the cooldown runs out.
+fn update_hotbar_display(
+    player_query: Query<(&PlayerInventory, &HotbarCooldowns), With<Player>>,
+    item_query: Query<(&Item, &TextureAtlas, Option<&Sprite>)>,
+    mut icon_query: Query<
+        (
+            &HotbarSlotIcon,
+            &mut TextureAtlas,
+            &mut UiImage,
+            &mut Visibility,
+        ),
+        Without<Item>,
+    >,
+    mut count_query: Query<(&HotbarSlotCount, &mut Text)>,
+    mut fill_query: Query<(&HotbarCooldownFill, &mut Style)>,
+) {
+    let Ok((inventory, cooldowns)) = player_query.get_single() else {
+        return;
+    };
+    let item_in = |slot: usize| {
+        inventory
+            .get(hotbar_slot(slot))
+            .and_then(|entity| item_query.get(entity).ok())
+    };
+
+    for (icon, mut atlas, mut image, mut visibility) in icon_query.iter_mut() {
+        match item_in(icon.0) {
+            Some((_, item_atlas, sprite)) => {
+                atlas.index = item_atlas.index;
+                image.color = sprite.map_or(Color::WHITE, |sprite| sprite.color);
+                *visibility = Visibility::Inherited;
+            }
+            None => *visibility = Visibility::Hidden,
+        }
+    }
+    for (count, mut text) in count_query.iter_mut() {
+        text.sections[0].value = item_in(count.0)
+            .map(|(item, ..)| item.stack.to_string())
+            .unwrap_or_default();
+    }
+    for (fill, mut style) in fill_query.iter_mut() {
+        style.height = Val::Percent(cooldowns.fraction_left(fill.0) * 100.0);
+    }
+}
@@ -0,0 +1,653 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::collision::Collider;
+use crate::pickup::{drop_item, Pickup};
+use crate::player::Player;
+use crate::potion::{Potion, PotionStats, PotionType};
+use crate::state::GameState;
+use crate::world::InGameEntity;
+use crate::*;
+
+pub struct InventoryPlugin;
+
+/// An item handed to the player by a dialog or the workbench that still
+/// needs a place in the inventory, and how many of it there are.
+#[derive(Component)]
+pub struct DeliverToInventory(pub u32);
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ItemCategory {
+    Weapon,
+    Armor(ArmorSlot),
+    Consumable,
+    /// Salvaged from gear and used up by workbench recipes.
+    Material,
+}
+
+/// Where an armor piece is worn. One piece per slot can be worn at a time.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ArmorSlot {
+    Head,
+    Body,
+    Boots,
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Default)]
+pub enum Rarity {
+    #[default]
+    Common,
+    Uncommon,
+    Rare,
+    Epic,
+}
+
+/// Anything that can sit in an inventory slot. Items with the same name
+/// merge into one stack up to `max_stack`.
+#[derive(Component, Clone, Debug)]
+pub struct Item {
+    pub name: String,
+    pub category: ItemCategory,
+    pub rarity: Rarity,
+    pub stack: u32,
+    pub max_stack: u32,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EquipmentSlot {
+    Weapon,
+    Armor(ArmorSlot),
+    QuickUse(usize),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SlotId {
+    Bag(usize),
+    Equipment(EquipmentSlot),
+}
+
+#[derive(Component)]
+pub struct PlayerInventory {
+    pub bag: Vec<Option<Entity>>,
+    pub weapon: Option<Entity>,
+    /// Indexed by `ArmorSlot::index`.
+    pub armor: [Option<Entity>; ArmorSlot::ALL.len()],
+    pub quick_use: [Option<Entity>; QUICK_USE_SLOTS],
+}
+
+#[derive(Event, Clone, Copy, Debug)]
+pub enum InventoryAction {
+    Move { from: SlotId, to: SlotId },
+    Swap { a: SlotId, b: SlotId },
+    Drop { slot: SlotId },
+    Split { slot: SlotId, amount: u32 },
+}
+
+/// Sent once for every slot whose content changed, whatever changed it.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct InventoryChangedEvent {
+    pub slot: SlotId,
+    pub item: Option<Entity>,
+}
+
+impl Item {
+    pub fn weapon(name: &str) -> Self {
+        Self::new(name, ItemCategory::Weapon)
+    }
+
+    pub fn armor(name: &str, slot: ArmorSlot) -> Self {
+        Self::new(name, ItemCategory::Armor(slot))
+    }
+
+    pub fn consumable(name: &str) -> Self {
+        Self::new(name, ItemCategory::Consumable)
+    }
+
+    pub fn material(name: &str) -> Self {
+        Self::new(name, ItemCategory::Material)
+    }
+
+    fn new(name: &str, category: ItemCategory) -> Self {
+        Self {
+            name: name.to_string(),
+            category,
+            rarity: Rarity::Common,
+            stack: 1,
+            max_stack: category.max_stack(),
+        }
+    }
+
+    pub fn with_rarity(mut self, rarity: Rarity) -> Self {
+        self.rarity = rarity;
+        self
+    }
+
+    pub fn stacks_with(&self, other: &Item) -> bool {
+        self.max_stack > 1
+            && self.name == other.name
+            && self.category == other.category
+            && self.rarity == other.rarity
+    }
+}
+
+impl ItemCategory {
+    pub fn max_stack(&self) -> u32 {
+        match self {
+            ItemCategory::Weapon | ItemCategory::Armor(_) => 1,
+            ItemCategory::Consumable => MAX_CONSUMABLE_STACK,
+            ItemCategory::Material => MAX_MATERIAL_STACK,
+        }
+    }
+}
+
+impl Rarity {
+    /// Rolls a rarity, with the better ones becoming likelier in later waves.
+    pub fn roll(wave_number: u32) -> Self {
+        let roll = rand::random::<f32>() - RARITY_BONUS_PER_WAVE * wave_number as f32;
+        match roll {
+            r if r < 0.05 => Rarity::Epic,
+            r if r < 0.2 => Rarity::Rare,
+            r if r < 0.5 => Rarity::Uncommon,
+            _ => Rarity::Common,
+        }
+    }
+
+    /// How much better than common the stats of an item are.
+    pub fn stat_multiplier(&self) -> f32 {
+        match self {
+            Rarity::Common => 1.0,
+            Rarity::Uncommon => 1.15,
+            Rarity::Rare => 1.3,
+            Rarity::Epic => 1.5,
+        }
+    }
+
+    pub fn price_multiplier(&self) -> f32 {
+        match self {
+            Rarity::Common => 1.0,
+            Rarity::Uncommon => 1.5,
+            Rarity::Rare => 2.5,
+            Rarity::Epic => 4.0,
+        }
+    }
+}
+
+impl ArmorSlot {
+    pub const ALL: [ArmorSlot; 3] = [ArmorSlot::Head, ArmorSlot::Body, ArmorSlot::Boots];
+
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ArmorSlot::Head => "Head",
+            ArmorSlot::Body => "Body",
+            ArmorSlot::Boots => "Boots",
+        }
+    }
+}
+
+impl EquipmentSlot {
+    pub fn accepts(&self, category: ItemCategory) -> bool {
+        match (self, category) {
+            (EquipmentSlot::Weapon, ItemCategory::Weapon) => true,
+            (EquipmentSlot::Armor(worn), ItemCategory::Armor(piece)) => *worn == piece,
+            (EquipmentSlot::QuickUse(_), ItemCategory::Consumable) => true,
+            _ => false,
+        }
+    }
+}
+
+impl SlotId {
+    pub fn accepts(&self, category: ItemCategory) -> bool {
+        match self {
+            SlotId::Bag(_) => true,
+            SlotId::Equipment(slot) => slot.accepts(category),
+        }
+    }
+}
+
+impl Default for PlayerInventory {
+    fn default() -> Self {
+        Self {
+            bag: vec![None; INVENTORY_BAG_SIZE],
+            weapon: None,
+            armor: [None; ArmorSlot::ALL.len()],
+            quick_use: [None; QUICK_USE_SLOTS],
+        }
+    }
+}
+
+impl PlayerInventory {
+    pub fn get(&self, slot: SlotId) -> Option<Entity> {
+        match slot {
+            SlotId::Bag(index) => self.bag.get(index).copied().flatten(),
+            SlotId::Equipment(EquipmentSlot::Weapon) => self.weapon,
+            SlotId::Equipment(EquipmentSlot::Armor(slot)) => self.armor[slot.index()],
+            SlotId::Equipment(EquipmentSlot::QuickUse(index)) => {
+                self.quick_use.get(index).copied().flatten()
+            }
+        }
+    }
+
+    pub fn set(
+        &mut self,
+        slot: SlotId,
+        item: Option<Entity>,
+        ev_changed: &mut EventWriter<InventoryChangedEvent>,
+    ) {
+        let target = match slot {
+            SlotId::Bag(index) => self.bag.get_mut(index),
+            SlotId::Equipment(EquipmentSlot::Weapon) => Some(&mut self.weapon),
+            SlotId::Equipment(EquipmentSlot::Armor(slot)) => Some(&mut self.armor[slot.index()]),
+            SlotId::Equipment(EquipmentSlot::QuickUse(index)) => self.quick_use.get_mut(index),
+        };
+        if let Some(target) = target {
+            *target = item;
+            ev_changed.send(InventoryChangedEvent { slot, item });
+        }
+    }
+
+    /// Every occupied slot, equipment first.
+    pub fn slots(&self) -> impl Iterator<Item = (SlotId, Entity)> + '_ {
+        let weapon = [(SlotId::Equipment(EquipmentSlot::Weapon), self.weapon)];
+        let armor = ArmorSlot::ALL.iter().map(|slot| {
+            (
+                SlotId::Equipment(EquipmentSlot::Armor(*slot)),
+                self.armor[slot.index()],
+            )
+        });
+        let quick_use = self
+            .quick_use
+            .iter()
+            .enumerate()
+            .map(|(index, item)| (SlotId::Equipment(EquipmentSlot::QuickUse(index)), *item));
+        let bag = self
+            .bag
+            .iter()
+            .enumerate()
+            .map(|(index, item)| (SlotId::Bag(index), *item));
+        weapon
+            .into_iter()
+            .chain(armor)
+            .chain(quick_use)
+            .chain(bag)
+            .filter_map(|(slot, item)| item.map(|item| (slot, item)))
+    }
+
+    /// Every occupied bag slot, leaving out anything equipped.
+    pub fn bag_slots(&self) -> impl Iterator<Item = (SlotId, Entity)> + '_ {
+        self.bag
+            .iter()
+            .enumerate()
+            .filter_map(|(index, item)| item.map(|item| (SlotId::Bag(index), item)))
+    }
+
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.slots().any(|(_, item)| item == entity)
+    }
+
+    pub fn first_free_bag_slot(&self) -> Option<SlotId> {
+        self.bag.iter().position(Option::is_none).map(SlotId::Bag)
+    }
+
+    /// Worn armor pieces, in slot order.
+    pub fn worn_armor(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.armor.iter().flatten().copied()
+    }
+
+    /// Next bag slot after `after` holding an item that `matches`, wrapping
+    /// around.
+    pub fn next_in_bag(&self, after: usize, matches: impl Fn(Entity) -> bool) -> Option<usize> {
+        let len = self.bag.len();
+        (1..=len)
+            .map(|offset| (after + offset) % len)
+            .find(|index| self.bag[*index].is_some_and(&matches))
+    }
+}
+
+impl Plugin for InventoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<InventoryAction>()
+            .add_event::<InventoryChangedEvent>()
+            .add_systems(
+                Update,
+                apply_inventory_actions
+                    .run_if(in_state(GameState::InGame).or_else(in_state(GameState::Paused))),
+            )
+            .add_systems(
+                Update,
+                deliver_to_inventory.run_if(any_with_component::<DeliverToInventory>),
+            );
+    }
+}
+
+/// Puts `entity` into the inventory, topping up matching stacks first.
+/// Returns false and leaves the inventory untouched if there is no room.
+pub fn add_to_inventory(
+    commands: &mut Commands,
+    inventory: &mut PlayerInventory,
+    entity: Entity,
+    item_query: &mut Query<&mut Item>,
+    ev_changed: &mut EventWriter<InventoryChangedEvent>,
+) -> bool {
+    let Ok(incoming) = item_query.get(entity).cloned() else {
+        return false;
+    };
+
+    let mut remaining = incoming.stack;
+    let stack_slots = inventory.slots().collect::<Vec<_>>();
+    let capacity = stack_slots
+        .iter()
+        .filter_map(|(_, other)| item_query.get(*other).ok())
+        .filter(|other| other.stacks_with(&incoming))
+        .map(|other| other.max_stack.saturating_sub(other.stack))
+        .sum::<u32>();
+    let free_slot = inventory.first_free_bag_slot();
+    if capacity < remaining && free_slot.is_none() {
+        return false;
+    }
+
+    for (slot, other) in stack_slots {
+        if remaining == 0 {
+            break;
+        }
+        let Ok(mut existing) = item_query.get_mut(other) else {
+            continue;
+        };
+        if !existing.stacks_with(&incoming) {
+            continue;
+        }
+        let moved = remaining.min(existing.max_stack.saturating_sub(existing.stack));
+        if moved > 0 {
+            existing.stack += moved;
+            remaining -= moved;
+            ev_changed.send(InventoryChangedEvent {
+                slot,
+                item: Some(other),
+            });
+        }
+    }
+
+    if remaining == 0 {
+        commands.entity(entity).despawn_recursive();
+    } else if let Some(slot) = free_slot {
+        if let Ok(mut item) = item_query.get_mut(entity) {
+            item.stack = remaining;
+        }
+        inventory.set(slot, Some(entity), ev_changed);
+    }
+    true
+}
+
+/// Puts `entity` into the inventory, or drops it next to `position` when
+/// there is no room.
+pub fn add_or_drop(
+    commands: &mut Commands,
+    inventory: &mut PlayerInventory,
+    entity: Entity,
+    position: Vec3,
+    item_query: &mut Query<&mut Item>,
+    ev_changed: &mut EventWriter<InventoryChangedEvent>,
+) {
+    if add_to_inventory(commands, inventory, entity, item_query, ev_changed) {
+        return;
+    }
+    let Ok(item) = item_query.get(entity) else {
+        return;
+    };
+    drop_item(commands, entity, item.category, position);
+}
+
+/// Puts delivered items into the inventory, or at the player's feet when
+/// there is no room.
+fn deliver_to_inventory(
+    mut commands: Commands,
+    delivery_query: Query<(Entity, &DeliverToInventory)>,
+    mut player_query: Query<(&Transform, &mut PlayerInventory), With<Player>>,
+    mut item_query: Query<&mut Item>,
+    mut ev_changed: EventWriter<InventoryChangedEvent>,
+) {
+    let Ok((player_transform, mut inventory)) = player_query.get_single_mut() else {
+        return;
+    };
+    for (entity, delivery) in delivery_query.iter() {
+        commands.entity(entity).remove::<DeliverToInventory>();
+        if let Ok(mut item) = item_query.get_mut(entity) {
+            item.stack = delivery.0;
+        }
+        add_or_drop(
+            &mut commands,
+            &mut inventory,
+            entity,
+            player_transform.translation,
+            &mut item_query,
+            &mut ev_changed,
+        );
+    }
+}
+
+/// Uses up one item from the stack in `slot`, despawning it when empty.
+pub fn consume_one(
+    commands: &mut Commands,
+    inventory: &mut PlayerInventory,
+    slot: SlotId,
+    item_query: &mut Query<&mut Item>,
+    ev_changed: &mut EventWriter<InventoryChangedEvent>,
+) {
+    let Some(entity) = inventory.get(slot) else {
+        return;
+    };
+    let Ok(mut item) = item_query.get_mut(entity) else {
+        return;
+    };
+    item.stack = item.stack.saturating_sub(1);
+    if item.stack == 0 {
+        commands.entity(entity).despawn_recursive();
+        inventory.set(slot, None, ev_changed);
+    } else {
+        ev_changed.send(InventoryChangedEvent {
+            slot,
+            item: Some(entity),
+        });
+    }
+}
+
+fn apply_inventory_actions(
+    mut commands: Commands,
+    mut player_query: Query<(&Transform, &mut PlayerInventory), With<Player>>,
+    mut item_query: Query<&mut Item>,
+    mut actions: EventReader<InventoryAction>,
+    mut ev_changed: EventWriter<InventoryChangedEvent>,
+) {
+    let Ok((player_transform, mut inventory)) = player_query.get_single_mut() else {
+        actions.clear();
+        return;
+    };
+
+    for action in actions.read() {
+        match *action {
+            InventoryAction::Move { from, to } => {
+                move_item(&mut inventory, from, to, &mut item_query, &mut ev_changed);
+            }
+            InventoryAction::Swap { a, b } => {
+                swap_items(&mut inventory, a, b, &item_query, &mut ev_changed);
+            }
+            InventoryAction::Drop { slot } => {
+                let Some(entity) = inventory.get(slot) else {
+                    continue;
+                };
+                let Ok(item) = item_query.get(entity) else {
+                    continue;
+                };
+                inventory.set(slot, None, &mut ev_changed);
+                drop_item(
+                    &mut commands,
+                    entity,
+                    item.category,
+                    player_transform.translation,
+                );
+            }
+            InventoryAction::Split { slot, amount } => {
+                split_stack(
+                    &mut commands,
+                    &mut inventory,
+                    slot,
+                    amount,
+                    &mut item_query,
+                    &mut ev_changed,
+                );
+            }
+        }
+    }
+}
+
+fn move_item(
+    inventory: &mut PlayerInventory,
+    from: SlotId,
+    to: SlotId,
+    item_query: &mut Query<&mut Item>,
+    ev_changed: &mut EventWriter<InventoryChangedEvent>,
+) {
+    let Some(entity) = inventory.get(from) else {
+        return;
+    };
+    let Ok(item) = item_query.get(entity).cloned() else {
+        return;
+    };
+    if from == to || !to.accepts(item.category) {
+        return;
+    }
+
+    match inventory.get(to) {
+        None => {
+            inventory.set(from, None, ev_changed);
+            inventory.set(to, Some(entity), ev_changed);
+        }
+        // Moving onto a matching stack merges as much as fits
+        Some(target) => {
+            let Ok([mut source, mut destination]) = item_query.get_many_mut([entity, target])
+            else {
+                return;
+            };
+            if !destination.stacks_with(&source) {
+                return;
+            }
+            let moved = source
+                .stack
+                .min(destination.max_stack.saturating_sub(destination.stack));
+            destination.stack += moved;
+            source.stack -= moved;
+            ev_changed.send(InventoryChangedEvent {
+                slot: to,
+                item: Some(target),
+            });
+            if source.stack == 0 {
+                inventory.set(from, None, ev_changed);
+            } else {
+                ev_changed.send(InventoryChangedEvent {
+                    slot: from,
+                    item: Some(entity),
+                });
+            }
+        }
+    }
+}
+
+fn swap_items(
+    inventory: &mut PlayerInventory,
+    a: SlotId,
+    b: SlotId,
+    item_query: &Query<&mut Item>,
+    ev_changed: &mut EventWriter<InventoryChangedEvent>,
+) {
+    let (item_a, item_b) = (inventory.get(a), inventory.get(b));
+    let fits = |item: Option<Entity>, slot: SlotId| {
+        item.map_or(true, |entity| {
+            item_query
+                .get(entity)
+                .is_ok_and(|item| slot.accepts(item.category))
+        })
+    };
+    if a == b || !fits(item_a, b) || !fits(item_b, a) {
+        return;
+    }
+    inventory.set(a, item_b, ev_changed);
+    inventory.set(b, item_a, ev_changed);
+}
+
+/// Moves `amount` items off the stack in `slot` into a new stack in the
+/// first free bag slot.
+fn split_stack(
+    commands: &mut Commands,
+    inventory: &mut PlayerInventory,
+    slot: SlotId,
+    amount: u32,
+    item_query: &mut Query<&mut Item>,
+    ev_changed: &mut EventWriter<InventoryChangedEvent>,
+) {
+    let (Some(source), Some(target_slot)) = (inventory.get(slot), inventory.first_free_bag_slot())
+    else {
+        return;
+    };
+    let Ok(mut item) = item_query.get_mut(source) else {
+        return;
+    };
+    if amount == 0 || amount >= item.stack {
+        return;
+    }
+    item.stack -= amount;
+
+    let mut split = item.clone();
+    split.stack = amount;
+    let target = commands.spawn((split, InGameEntity)).id();
+    commands.add(move |world: &mut World| copy_item_visuals(world, source, target));
+
+    ev_changed.send(InventoryChangedEvent {
+        slot,
+        item: Some(source),
+    });
+    inventory.set(target_slot, Some(target), ev_changed);
+}
+
+/// Only consumables stack, so a split stack only needs the potion and
+/// sprite components of its source.
+fn copy_item_visuals(world: &mut World, source: Entity, target: Entity) {
+    let Some(source) = world.get_entity(source) else {
+        return;
+    };
+    let potion = source
+        .get::<PotionStats>()
+        .cloned()
+        .zip(source.get::<PotionType>().copied());
+    let sprite = source
+        .get::<Handle<Image>>()
+        .cloned()
+        .zip(source.get::<TextureAtlas>().cloned());
+
+    let Some(mut target) = world.get_entity_mut(target) else {
+        return;
+    };
+    if let Some((stats, potion_type)) = potion {
+        target.insert((Potion, stats, potion_type));
+    }
+    if let Some((texture, atlas)) = sprite {
+        target.insert((
+            SpriteBundle {
+                texture,
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            atlas,
+        ));
+    }
+}
+
+/// Strips the ground-only components off an item that was just collected.
+pub fn stow_item(commands: &mut Commands, entity: Entity) {
+    commands
+        .entity(entity)
+        .remove::<(Pickup, Collider)>()
+        .insert(Visibility::Hidden);
+}
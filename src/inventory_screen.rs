@@ -0,0 +1,739 @@
+use bevy::prelude::*;
+
+use crate::armor::ArmorStats;
+use crate::dialog::ActiveDialog;
+use crate::gun::{Attachments, BulletStats, GunStats, OnHitEffect, WeaponSummary};
+use crate::hotbar::{hotbar_slot, HOTBAR_KEYS};
+use crate::inventory::{
+    ArmorSlot, EquipmentSlot, InventoryAction, Item, ItemCategory, PlayerInventory, SlotId,
+};
+use crate::items::armor_sets;
+use crate::player::Player;
+use crate::potion::{PotionStats, PotionType};
+use crate::state::GameState;
+use crate::world::InGameEntity;
+use crate::*;
+
+pub struct InventoryScreenPlugin;
+
+/// Whether the inventory screen is showing, which slot is highlighted and
+/// which one is being dragged. Both index into `screen_slots()`.
+#[derive(Resource, Default)]
+pub struct InventoryScreenState {
+    pub open: bool,
+    pub selected: usize,
+    pub dragging: Option<usize>,
+}
+
+#[derive(Component)]
+struct InventoryScreen;
+#[derive(Component)]
+struct InventorySlotButton(usize);
+#[derive(Component)]
+struct SlotIcon(usize);
+#[derive(Component)]
+struct SlotStackText(usize);
+#[derive(Component)]
+struct InventoryTooltip;
+#[derive(Component)]
+struct DragIcon;
+
+type ItemDetails<'a> = (
+    &'a Item,
+    &'a TextureAtlas,
+    Option<&'a GunStats>,
+    Option<&'a BulletStats>,
+    Option<&'a ArmorStats>,
+    Option<&'a PotionStats>,
+    Option<&'a PotionType>,
+);
+
+impl Plugin for InventoryScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InventoryScreenState>()
+            .add_systems(
+                Update,
+                toggle_inventory_screen
+                    .run_if(in_state(GameState::InGame).or_else(in_state(GameState::Paused))),
+            )
+            .add_systems(
+                Update,
+                (
+                    handle_inventory_navigation,
+                    handle_inventory_mouse,
+                    handle_inventory_commands,
+                    update_inventory_slots,
+                    update_drag_icon,
+                    update_inventory_tooltip,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Paused).and_then(inventory_screen_open)),
+            )
+            .add_systems(OnExit(GameState::Paused), close_inventory_screen);
+    }
+}
+
+fn inventory_screen_open(state: Res<InventoryScreenState>) -> bool {
+    state.open
+}
+
+/// Equipment first, then the bag, in the order they are laid out on screen.
+fn screen_slots() -> Vec<SlotId> {
+    let mut slots = vec![SlotId::Equipment(EquipmentSlot::Weapon)];
+    slots.extend(
+        ArmorSlot::ALL
+            .iter()
+            .map(|slot| SlotId::Equipment(EquipmentSlot::Armor(*slot))),
+    );
+    slots.extend((0..QUICK_USE_SLOTS).map(|i| SlotId::Equipment(EquipmentSlot::QuickUse(i))));
+    slots.extend((0..INVENTORY_BAG_SIZE).map(SlotId::Bag));
+    slots
+}
+
+fn screen_columns() -> usize {
+    QUICK_USE_SLOTS + ArmorSlot::ALL.len() + 1
+}
+
+fn slot_label(slot: SlotId) -> String {
+    match slot {
+        SlotId::Equipment(EquipmentSlot::Weapon) => "Weapon".to_string(),
+        SlotId::Equipment(EquipmentSlot::Armor(slot)) => slot.name().to_string(),
+        SlotId::Equipment(EquipmentSlot::QuickUse(i)) => format!("Quick Use {}", i + 1),
+        SlotId::Bag(i) => format!("Bag {}", i + 1),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn toggle_inventory_screen(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut screen_state: ResMut<InventoryScreenState>,
+    current_state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    active_dialog: Res<ActiveDialog>,
+    handle: Res<GlobalTextureAtlas>,
+    font: Res<UiFont>,
+) {
+    let toggle = keyboard_input.just_pressed(KeyCode::KeyI);
+    if screen_state.open {
+        // Despawning happens on leaving the paused state
+        if toggle || keyboard_input.just_pressed(KeyCode::Escape) {
+            next_state.set(GameState::InGame);
+        }
+    } else if toggle && *current_state.get() == GameState::InGame && active_dialog.0.is_none() {
+        spawn_inventory_screen(&mut commands, &handle, &font.0);
+        screen_state.open = true;
+        screen_state.selected = 0;
+        screen_state.dragging = None;
+        next_state.set(GameState::Paused);
+    }
+}
+
+fn close_inventory_screen(
+    mut commands: Commands,
+    mut screen_state: ResMut<InventoryScreenState>,
+    screen_query: Query<Entity, With<InventoryScreen>>,
+) {
+    if !screen_state.open {
+        return;
+    }
+    for entity in screen_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    screen_state.open = false;
+}
+
+fn spawn_inventory_screen(
+    commands: &mut Commands,
+    handle: &GlobalTextureAtlas,
+    font: &Handle<Font>,
+) {
+    let text_style = |font_size: f32| TextStyle {
+        font: font.clone(),
+        font_size,
+        color: Color::WHITE,
+    };
+    let grid_width = screen_columns() as f32 * (INVENTORY_SLOT_SIZE + 8.0);
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    position_type: PositionType::Absolute,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    column_gap: Val::Px(20.0),
+                    ..default()
+                },
+                background_color: Color::srgba(0.0, 0.0, 0.0, 0.8).into(),
+                z_index: ZIndex::Global(10),
+                ..default()
+            },
+            InventoryScreen,
+            InGameEntity,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        padding: UiRect::all(Val::Px(10.0)),
+                        row_gap: Val::Px(10.0),
+                        ..default()
+                    },
+                    background_color: Color::srgba(0.1, 0.1, 0.1, 0.9).into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section("Inventory", text_style(32.0)));
+                    parent
+                        .spawn(NodeBundle {
+                            style: Style {
+                                width: Val::Px(grid_width),
+                                flex_wrap: FlexWrap::Wrap,
+                                ..default()
+                            },
+                            ..default()
+                        })
+                        .with_children(|parent| {
+                            for (index, slot) in screen_slots().into_iter().enumerate() {
+                                spawn_slot(parent, index, slot, handle, text_style(16.0));
+                            }
+                        });
+                    parent.spawn(TextBundle::from_section(
+                        format!(
+                            "Arrows/mouse: select  Enter/click: equip  Drag: move\n\
+                             1-{}: put on quick use  G: drop  I: close",
+                            QUICK_USE_SLOTS
+                        ),
+                        text_style(14.0),
+                    ));
+                });
+
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Px(300.0),
+                        min_height: Val::Px(200.0),
+                        padding: UiRect::all(Val::Px(10.0)),
+                        ..default()
+                    },
+                    background_color: Color::srgba(0.1, 0.1, 0.1, 0.9).into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn((
+                        TextBundle::from_section("", text_style(18.0)),
+                        InventoryTooltip,
+                    ));
+                });
+
+            parent.spawn((
+                ImageBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        width: Val::Px(INVENTORY_SLOT_SIZE),
+                        height: Val::Px(INVENTORY_SLOT_SIZE),
+                        ..default()
+                    },
+                    image: UiImage::new(handle.image.clone().unwrap_or_default()),
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+                TextureAtlas {
+                    layout: handle.layout.clone().unwrap_or_default(),
+                    index: 0,
+                },
+                DragIcon,
+            ));
+        });
+}
+
+fn spawn_slot(
+    parent: &mut ChildBuilder,
+    index: usize,
+    slot: SlotId,
+    handle: &GlobalTextureAtlas,
+    text_style: TextStyle,
+) {
+    let background = match slot {
+        SlotId::Equipment(_) => Color::srgb(0.2, 0.25, 0.35),
+        SlotId::Bag(_) => Color::srgb(0.2, 0.2, 0.2),
+    };
+    parent
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    width: Val::Px(INVENTORY_SLOT_SIZE),
+                    height: Val::Px(INVENTORY_SLOT_SIZE),
+                    margin: UiRect::all(Val::Px(4.0)),
+                    border: UiRect::all(Val::Px(3.0)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: background.into(),
+                border_color: BorderColor(Color::BLACK),
+                ..default()
+            },
+            InventorySlotButton(index),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                ImageBundle {
+                    style: Style {
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        ..default()
+                    },
+                    image: UiImage::new(handle.image.clone().unwrap_or_default()),
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+                TextureAtlas {
+                    layout: handle.layout.clone().unwrap_or_default(),
+                    index: 0,
+                },
+                SlotIcon(index),
+            ));
+            parent.spawn((
+                TextBundle::from_section("", text_style).with_style(Style {
+                    position_type: PositionType::Absolute,
+                    right: Val::Px(2.0),
+                    bottom: Val::Px(0.0),
+                    ..default()
+                }),
+                SlotStackText(index),
+            ));
+        });
+}
+
+fn handle_inventory_navigation(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut screen_state: ResMut<InventoryScreenState>,
+) {
+    let last = screen_slots().len() - 1;
+    let columns = screen_columns();
+    let selected = screen_state.selected;
+
+    if keyboard_input.just_pressed(KeyCode::ArrowLeft) {
+        screen_state.selected = selected.saturating_sub(1);
+    }
+    if keyboard_input.just_pressed(KeyCode::ArrowRight) {
+        screen_state.selected = (selected + 1).min(last);
+    }
+    if keyboard_input.just_pressed(KeyCode::ArrowUp) {
+        screen_state.selected = selected.saturating_sub(columns);
+    }
+    if keyboard_input.just_pressed(KeyCode::ArrowDown) {
+        screen_state.selected = (selected + columns).min(last);
+    }
+}
+
+/// Pressing on an item picks it up; releasing over another slot moves it
+/// there, releasing over the same slot equips it like Enter does.
+fn handle_inventory_mouse(
+    interaction_query: Query<(&Interaction, &InventorySlotButton), Changed<Interaction>>,
+    button_query: Query<(&Interaction, &InventorySlotButton)>,
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    mut screen_state: ResMut<InventoryScreenState>,
+    player_query: Query<&PlayerInventory, With<Player>>,
+    item_query: Query<&Item>,
+    mut ev_inventory_action: EventWriter<InventoryAction>,
+) {
+    let Ok(inventory) = player_query.get_single() else {
+        return;
+    };
+    let slots = screen_slots();
+    for (interaction, button) in interaction_query.iter() {
+        match interaction {
+            Interaction::Hovered => screen_state.selected = button.0,
+            Interaction::Pressed => {
+                screen_state.selected = button.0;
+                if inventory.get(slots[button.0]).is_some() {
+                    screen_state.dragging = Some(button.0);
+                }
+            }
+            Interaction::None => {}
+        }
+    }
+
+    if !mouse_input.just_released(MouseButton::Left) {
+        return;
+    }
+    let Some(from) = screen_state.dragging.take() else {
+        return;
+    };
+    let target = button_query
+        .iter()
+        .find(|(interaction, _)| **interaction == Interaction::Hovered)
+        .map(|(_, button)| button.0);
+    match target {
+        Some(to) if to == from => {
+            send_equip_action(
+                inventory,
+                slots[from],
+                &item_query,
+                &mut ev_inventory_action,
+            );
+        }
+        Some(to) => {
+            let (from, to) = (slots[from], slots[to]);
+            let item = |slot: SlotId| {
+                inventory
+                    .get(slot)
+                    .and_then(|entity| item_query.get(entity).ok())
+            };
+            // Matching stacks merge, anything else trades places
+            let merges = matches!((item(from), item(to)), (Some(a), Some(b)) if a.stacks_with(b));
+            ev_inventory_action.send(if merges {
+                InventoryAction::Move { from, to }
+            } else {
+                InventoryAction::Swap { a: from, b: to }
+            });
+        }
+        None => {}
+    }
+}
+
+fn handle_inventory_commands(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    screen_state: Res<InventoryScreenState>,
+    player_query: Query<&PlayerInventory, With<Player>>,
+    item_query: Query<&Item>,
+    mut ev_inventory_action: EventWriter<InventoryAction>,
+) {
+    let Ok(inventory) = player_query.get_single() else {
+        return;
+    };
+    let slot = screen_slots()[screen_state.selected];
+
+    if keyboard_input.just_pressed(KeyCode::Enter) {
+        send_equip_action(inventory, slot, &item_query, &mut ev_inventory_action);
+    }
+    if keyboard_input.just_pressed(KeyCode::KeyG) && inventory.get(slot).is_some() {
+        ev_inventory_action.send(InventoryAction::Drop { slot });
+    }
+    // Number keys put the selected consumable on that quick-use slot
+    for (index, key) in HOTBAR_KEYS.iter().take(QUICK_USE_SLOTS).enumerate() {
+        let target = hotbar_slot(index);
+        if keyboard_input.just_pressed(*key) && slot != target && inventory.get(slot).is_some() {
+            ev_inventory_action.send(InventoryAction::Swap { a: slot, b: target });
+        }
+    }
+}
+
+/// Bag items go to the equipment slot for their category, swapping out what
+/// was there. Equipped items go back to the bag.
+fn send_equip_action(
+    inventory: &PlayerInventory,
+    slot: SlotId,
+    item_query: &Query<&Item>,
+    ev_inventory_action: &mut EventWriter<InventoryAction>,
+) {
+    let Some(item) = inventory
+        .get(slot)
+        .and_then(|entity| item_query.get(entity).ok())
+    else {
+        return;
+    };
+    let target = match slot {
+        SlotId::Bag(_) => match item.category {
+            ItemCategory::Weapon => Some(SlotId::Equipment(EquipmentSlot::Weapon)),
+            ItemCategory::Armor(slot) => Some(SlotId::Equipment(EquipmentSlot::Armor(slot))),
+            ItemCategory::Consumable => {
+                let free = (0..QUICK_USE_SLOTS).find(|i| inventory.quick_use[*i].is_none());
+                Some(SlotId::Equipment(EquipmentSlot::QuickUse(
+                    free.unwrap_or(0),
+                )))
+            }
+            ItemCategory::Material => None,
+        },
+        SlotId::Equipment(_) => inventory.first_free_bag_slot(),
+    };
+    if let Some(target) = target {
+        ev_inventory_action.send(InventoryAction::Swap { a: slot, b: target });
+    }
+}
+
+fn update_inventory_slots(
+    screen_state: Res<InventoryScreenState>,
+    player_query: Query<&PlayerInventory, With<Player>>,
+    item_query: Query<ItemDetails>,
+    sprite_query: Query<Option<&Sprite>, With<Item>>,
+    mut button_query: Query<(&InventorySlotButton, &mut BorderColor)>,
+    mut icon_query: Query<
+        (&SlotIcon, &mut TextureAtlas, &mut UiImage, &mut Visibility),
+        Without<Item>,
+    >,
+    mut text_query: Query<(&SlotStackText, &mut Text)>,
+) {
+    let Ok(inventory) = player_query.get_single() else {
+        return;
+    };
+    let slots = screen_slots();
+
+    for (button, mut border) in button_query.iter_mut() {
+        *border = if button.0 == screen_state.selected {
+            BorderColor(Color::srgb(1.0, 0.8, 0.2))
+        } else {
+            BorderColor(Color::BLACK)
+        };
+    }
+
+    for (icon, mut atlas, mut image, mut visibility) in icon_query.iter_mut() {
+        let item = inventory.get(slots[icon.0]);
+        match item.and_then(|entity| item_query.get(entity).ok()) {
+            Some((_, item_atlas, ..)) => {
+                let sprite = item.and_then(|entity| sprite_query.get(entity).ok().flatten());
+                atlas.index = item_atlas.index;
+                image.color = sprite.map_or(Color::WHITE, |sprite| sprite.color);
+                *visibility = Visibility::Inherited;
+            }
+            None => *visibility = Visibility::Hidden,
+        }
+    }
+
+    for (stack_text, mut text) in text_query.iter_mut() {
+        text.sections[0].value = inventory
+            .get(slots[stack_text.0])
+            .and_then(|entity| item_query.get(entity).ok())
+            .filter(|(item, ..)| item.stack > 1)
+            .map(|(item, ..)| item.stack.to_string())
+            .unwrap_or_default();
+    }
+}
+
+/// Keeps the dragged item's icon under the cursor.
+#[allow(clippy::type_complexity)]
+fn update_drag_icon(
+    screen_state: Res<InventoryScreenState>,
+    window_query: Query<&Window>,
+    player_query: Query<&PlayerInventory, With<Player>>,
+    item_query: Query<(&TextureAtlas, Option<&Sprite>), With<Item>>,
+    mut icon_query: Query<
+        (&mut Style, &mut TextureAtlas, &mut UiImage, &mut Visibility),
+        (With<DragIcon>, Without<Item>),
+    >,
+) {
+    let Ok((mut style, mut atlas, mut image, mut visibility)) = icon_query.get_single_mut() else {
+        return;
+    };
+    let dragged = screen_state
+        .dragging
+        .zip(player_query.get_single().ok())
+        .and_then(|(index, inventory)| inventory.get(screen_slots()[index]))
+        .and_then(|entity| item_query.get(entity).ok());
+    let cursor = window_query
+        .get_single()
+        .ok()
+        .and_then(|window| window.cursor_position());
+    let (Some((item_atlas, sprite)), Some(cursor)) = (dragged, cursor) else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+    atlas.index = item_atlas.index;
+    image.color = sprite.map_or(Color::WHITE, |sprite| sprite.color);
+    style.left = Val::Px(cursor.x - INVENTORY_SLOT_SIZE / 2.0);
+    style.top = Val::Px(cursor.y - INVENTORY_SLOT_SIZE / 2.0);
+    *visibility = Visibility::Inherited;
+}
+
+fn update_inventory_tooltip(
+    screen_state: Res<InventoryScreenState>,
+    player_query: Query<&PlayerInventory, With<Player>>,
+    item_query: Query<ItemDetails>,
+    on_hit_query: Query<&OnHitEffect>,
+    attachment_query: Query<&Attachments>,
+    mut tooltip_query: Query<&mut Text, With<InventoryTooltip>>,
+    font: Res<UiFont>,
+) {
+    let (Ok(inventory), Ok(mut text)) = (player_query.get_single(), tooltip_query.get_single_mut())
+    else {
+        return;
+    };
+    let slot = screen_slots()[screen_state.selected];
+    let mut tooltip = Tooltip::new(&font.0);
+    tooltip.line(&slot_label(slot));
+
+    let Some((entity, details)) = inventory
+        .get(slot)
+        .and_then(|entity| Some((entity, item_query.get(entity).ok()?)))
+    else {
+        tooltip.line("Empty");
+        text.sections = tooltip.sections;
+        return;
+    };
+
+    // Compare against whatever is equipped, unless this is it
+    let equipped = |equipped: Option<Entity>| {
+        equipped
+            .filter(|equipped| *equipped != entity)
+            .and_then(|equipped| item_query.get(equipped).ok())
+    };
+    let (item, _, gun_stats, bullet_stats, armor_stats, potion_stats, potion_type) = details;
+
+    tooltip.line("");
+    tooltip.line(&item.name);
+    tooltip.line(&format!("Rarity: {:?}", item.rarity));
+    if item.max_stack > 1 {
+        tooltip.line(&format!("Stack: {}/{}", item.stack, item.max_stack));
+    }
+    if let (Some(gun_stats), Some(bullet_stats)) = (gun_stats, bullet_stats) {
+        let summary = WeaponSummary::new(gun_stats, bullet_stats);
+        let other = equipped(inventory.weapon)
+            .and_then(|(_, _, gun, bullet, ..)| Some(WeaponSummary::new(gun?, bullet?)));
+        tooltip.stat("DPS", summary.dps, other.map(|o| o.dps), 1, true);
+        tooltip.stat("Range", summary.range, other.map(|o| o.range), 0, true);
+        tooltip.stat(
+            "Spread",
+            summary.spread_degrees,
+            other.map(|o| o.spread_degrees),
+            1,
+            false,
+        );
+        tooltip.line(&format!("Damage: {}", bullet_stats.damage));
+        tooltip.line(&format!("Bullets per shot: {}", gun_stats.bullets_per_shot));
+        tooltip.line(&format!(
+            "Firing interval: {:.2}s",
+            gun_stats.firing_interval
+        ));
+        if let Ok(OnHitEffect(status)) = on_hit_query.get(entity) {
+            tooltip.line(&format!(
+                "On hit: {:?} for {:.1}s",
+                status.kind, status.duration
+            ));
+        }
+        if let Ok(attachments) = attachment_query.get(entity) {
+            if !attachments.0.is_empty() {
+                let names = attachments
+                    .0
+                    .iter()
+                    .map(|attachment| attachment.name())
+                    .collect::<Vec<_>>();
+                tooltip.line(&format!("Attachments: {}", names.join(", ")));
+            }
+        }
+    }
+    if item.category == ItemCategory::Material {
+        tooltip.line("Crafting material, used at the workbench");
+    }
+    if let Some(stats) = armor_stats {
+        let other =
+            equipped(inventory.armor[stats.slot.index()]).and_then(|(_, _, _, _, armor, ..)| armor);
+        tooltip.stat(
+            "Defense",
+            stats.defense as f32,
+            other.map(|o| o.defense as f32),
+            0,
+            true,
+        );
+        tooltip.stat(
+            "Durability",
+            stats.max_durability as f32,
+            other.map(|o| o.max_durability as f32),
+            0,
+            true,
+        );
+        tooltip.line(&format!(
+            "Condition: {}/{}",
+            stats.durability, stats.max_durability
+        ));
+        if stats.is_broken() {
+            tooltip.push(
+                "Broken - repair at the blacksmith\n".to_string(),
+                Color::srgb(1.0, 0.2, 0.2),
+            );
+        }
+        for (stat, amount) in stats.modifiers.iter() {
+            tooltip.line(&stat.describe(*amount));
+        }
+        if let Some(set) = armor_sets()
+            .into_iter()
+            .find(|set| stats.set == Some(set.name))
+        {
+            let worn = inventory
+                .worn_armor()
+                .filter_map(|entity| item_query.get(entity).ok())
+                .filter(|(_, _, _, _, armor, ..)| {
+                    armor.is_some_and(|armor| armor.set == stats.set && !armor.is_broken())
+                })
+                .count();
+            tooltip.line(&format!("{} set ({} worn)", set.name, worn));
+            for bonus in set.bonuses.iter() {
+                let color = if worn >= bonus.pieces {
+                    Color::srgb(0.3, 1.0, 0.3)
+                } else {
+                    Color::srgb(0.5, 0.5, 0.5)
+                };
+                let modifiers = bonus
+                    .modifiers
+                    .iter()
+                    .map(|(stat, amount)| stat.describe(*amount))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                tooltip.push(format!("  {}: {}\n", bonus.pieces, modifiers), color);
+            }
+        }
+    }
+    if let (Some(stats), Some(potion_type)) = (potion_stats, potion_type) {
+        tooltip.line(&potion_type.description(stats));
+    }
+    text.sections = tooltip.sections;
+}
+
+/// Builds the tooltip text, one section per line plus a coloured section for
+/// each difference against the equipped item.
+struct Tooltip {
+    font: Handle<Font>,
+    sections: Vec<TextSection>,
+}
+
+impl Tooltip {
+    fn new(font: &Handle<Font>) -> Self {
+        Self {
+            font: font.clone(),
+            sections: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, value: String, color: Color) {
+        self.sections.push(TextSection::new(
+            value,
+            TextStyle {
+                font: self.font.clone(),
+                font_size: 18.0,
+                color,
+            },
+        ));
+    }
+
+    fn line(&mut self, value: &str) {
+        self.push(format!("{}\n", value), Color::WHITE);
+    }
+
+    fn stat(
+        &mut self,
+        label: &str,
+        value: f32,
+        equipped: Option<f32>,
+        precision: usize,
+        higher_is_better: bool,
+    ) {
+        self.push(format!("{}: {:.*}", label, precision, value), Color::WHITE);
+        if let Some(equipped) = equipped {
+            let delta = value - equipped;
+            if delta.abs() >= 0.5 * 10f32.powi(-(precision as i32)) {
+                let color = if (delta > 0.0) == higher_is_better {
+                    Color::srgb(0.2, 0.9, 0.2)
+                } else {
+                    Color::srgb(0.9, 0.2, 0.2)
+                };
+                self.push(format!(" ({:+.*})", precision, delta), color);
+            }
+        }
+        self.push("\n".to_string(), Color::WHITE);
+    }
+}
@@ -0,0 +1,540 @@
+use bevy::prelude::*;
+
+use crate::armor::{Armor, ArmorBundle, ArmorStats};
+use crate::effects::Stat;
+use crate::enemy::{StatusEffect, StatusKind};
+use crate::gun::{BulletStats, GunBundle, GunStats, OnHitEffect};
+use crate::inventory::{ArmorSlot, DeliverToInventory, Item, ItemCategory, Rarity};
+use crate::potion::{Potion, PotionBundle, PotionStats, PotionType};
+use crate::world::InGameEntity;
+use crate::*;
+
+/// Base stats for an item that can be created on demand, e.g. for a
+/// merchant's stock.
+#[derive(Clone)]
+pub struct ItemDefinition {
+    pub name: &'static str,
+    pub atlas_index: usize,
+    pub kind: ItemKind,
+}
+
+#[derive(Clone)]
+pub enum ItemKind {
+    Gun {
+        gun_stats: GunStats,
+        bullet_stats: BulletStats,
+        /// Status applied to enemies hit by the gun's bullets.
+        on_hit: Option<StatusEffect>,
+    },
+    Armor(ArmorStats),
+    Potion {
+        potion_type: PotionType,
+        potion_stats: PotionStats,
+    },
+    /// Tinted on top of the atlas sprite to tell materials apart.
+    Material(Color),
+}
+
+impl ItemKind {
+    pub fn category(&self) -> ItemCategory {
+        match self {
+            ItemKind::Gun { .. } => ItemCategory::Weapon,
+            ItemKind::Armor(stats) => ItemCategory::Armor(stats.slot),
+            ItemKind::Potion { .. } => ItemCategory::Consumable,
+            ItemKind::Material(_) => ItemCategory::Material,
+        }
+    }
+
+    /// The stats an item of this kind gets at `rarity` in `wave_number`.
+    /// Guns and armor get stronger with both; potions only with rarity.
+    pub fn scaled(&self, rarity: Rarity, wave_number: u32) -> ItemKind {
+        let scale = rarity.stat_multiplier()
+            * (1.0 + SHOP_STAT_SCALING_PER_WAVE * wave_number.saturating_sub(1) as f32);
+        let scaled = |value: u32| (value as f32 * scale).round() as u32;
+        match self {
+            ItemKind::Gun {
+                gun_stats,
+                bullet_stats,
+                on_hit,
+            } => ItemKind::Gun {
+                gun_stats: gun_stats.clone(),
+                bullet_stats: BulletStats {
+                    damage: scaled(bullet_stats.damage),
+                    ..bullet_stats.clone()
+                },
+                on_hit: *on_hit,
+            },
+            ItemKind::Armor(armor_stats) => ItemKind::Armor(ArmorStats {
+                defense: scaled(armor_stats.defense),
+                durability: scaled(armor_stats.durability),
+                max_durability: scaled(armor_stats.max_durability),
+                modifiers: armor_stats
+                    .modifiers
+                    .iter()
+                    .map(|(stat, amount)| (*stat, (*amount as f32 * scale).round() as i32))
+                    .collect(),
+                ..armor_stats.clone()
+            }),
+            ItemKind::Potion {
+                potion_type,
+                potion_stats,
+            } => {
+                let potency = rarity.stat_multiplier();
+                ItemKind::Potion {
+                    potion_type: *potion_type,
+                    potion_stats: PotionStats {
+                        effect_duration: potion_stats.effect_duration * potency,
+                        effect_amount: (potion_stats.effect_amount as f32 * potency).round() as u32,
+                    },
+                }
+            }
+            ItemKind::Material(tint) => ItemKind::Material(*tint),
+        }
+    }
+}
+
+pub fn item_definitions() -> Vec<ItemDefinition> {
+    vec![
+        ItemDefinition {
+            name: "Pistol",
+            atlas_index: 17,
+            kind: ItemKind::Gun {
+                gun_stats: GunStats {
+                    bullets_per_shot: NUM_BULLETS_PER_SHOT,
+                    firing_interval: BULLET_SPAWN_INTERVAL,
+                    bullet_spread: BULLET_SPREAD,
+                },
+                bullet_stats: BulletStats {
+                    speed: BULLET_SPEED,
+                    damage: BULLET_DAMAGE,
+                    lifespan: BULLET_TIME_SECS,
+                },
+                on_hit: None,
+            },
+        },
+        ItemDefinition {
+            name: "Rifle",
+            atlas_index: 17,
+            kind: ItemKind::Gun {
+                gun_stats: GunStats {
+                    bullets_per_shot: 2,
+                    firing_interval: 0.15,
+                    bullet_spread: 0.1,
+                },
+                bullet_stats: BulletStats {
+                    speed: 25,
+                    damage: 90,
+                    lifespan: 0.8,
+                },
+                on_hit: None,
+            },
+        },
+        ItemDefinition {
+            name: "Shotgun",
+            atlas_index: 56,
+            kind: ItemKind::Gun {
+                gun_stats: GunStats {
+                    bullets_per_shot: 10,
+                    firing_interval: 0.5,
+                    bullet_spread: 0.5,
+                },
+                bullet_stats: BulletStats {
+                    speed: 18,
+                    damage: 70,
+                    lifespan: 0.4,
+                },
+                on_hit: None,
+            },
+        },
+        ItemDefinition {
+            name: "Flare Gun",
+            atlas_index: 17,
+            kind: ItemKind::Gun {
+                gun_stats: GunStats {
+                    bullets_per_shot: 3,
+                    firing_interval: 0.4,
+                    bullet_spread: 0.3,
+                },
+                bullet_stats: BulletStats {
+                    speed: 16,
+                    damage: 40,
+                    lifespan: 0.6,
+                },
+                on_hit: Some(StatusEffect::new(StatusKind::Burn, 2.0, 5.0)),
+            },
+        },
+        ItemDefinition {
+            name: "Frost Rifle",
+            atlas_index: 17,
+            kind: ItemKind::Gun {
+                gun_stats: GunStats {
+                    bullets_per_shot: 1,
+                    firing_interval: 0.3,
+                    bullet_spread: 0.05,
+                },
+                bullet_stats: BulletStats {
+                    speed: 25,
+                    damage: 60,
+                    lifespan: 0.8,
+                },
+                on_hit: Some(StatusEffect::new(StatusKind::Slow, 2.5, 0.5)),
+            },
+        },
+        ItemDefinition {
+            name: "Leather Cap",
+            atlas_index: 58,
+            kind: ItemKind::Armor(ArmorStats::new(ArmorSlot::Head, 1, 15).in_set("Leather")),
+        },
+        ItemDefinition {
+            name: "Leather Armor",
+            atlas_index: 58,
+            kind: ItemKind::Armor(ArmorStats::new(ArmorSlot::Body, 2, 20).in_set("Leather")),
+        },
+        ItemDefinition {
+            name: "Leather Boots",
+            atlas_index: 58,
+            kind: ItemKind::Armor(
+                ArmorStats::new(ArmorSlot::Boots, 1, 15)
+                    .with_modifier(Stat::Speed, 1)
+                    .in_set("Leather"),
+            ),
+        },
+        ItemDefinition {
+            name: "Chain Coif",
+            atlas_index: 59,
+            kind: ItemKind::Armor(ArmorStats::new(ArmorSlot::Head, 2, 25).in_set("Chain")),
+        },
+        ItemDefinition {
+            name: "Chain Armor",
+            atlas_index: 59,
+            kind: ItemKind::Armor(ArmorStats::new(ArmorSlot::Body, 3, 30).in_set("Chain")),
+        },
+        ItemDefinition {
+            name: "Chain Greaves",
+            atlas_index: 59,
+            kind: ItemKind::Armor(ArmorStats::new(ArmorSlot::Boots, 2, 25).in_set("Chain")),
+        },
+        ItemDefinition {
+            name: "Spiked Helm",
+            atlas_index: 59,
+            kind: ItemKind::Armor(
+                ArmorStats::new(ArmorSlot::Head, 1, 20).with_modifier(Stat::Thorns, 20),
+            ),
+        },
+        ItemDefinition {
+            name: "Troll Hide",
+            atlas_index: 58,
+            kind: ItemKind::Armor(
+                ArmorStats::new(ArmorSlot::Body, 1, 25).with_modifier(Stat::MaxHealth, 30),
+            ),
+        },
+        ItemDefinition {
+            name: "Winged Boots",
+            atlas_index: 58,
+            kind: ItemKind::Armor(
+                ArmorStats::new(ArmorSlot::Boots, 0, 15).with_modifier(Stat::Speed, 3),
+            ),
+        },
+        ItemDefinition {
+            name: "Health Potion",
+            atlas_index: 57,
+            kind: ItemKind::Potion {
+                potion_type: PotionType::Health,
+                potion_stats: PotionStats {
+                    effect_duration: 2.0,
+                    effect_amount: 10,
+                },
+            },
+        },
+        ItemDefinition {
+            name: "Speed Potion",
+            atlas_index: 57,
+            kind: ItemKind::Potion {
+                potion_type: PotionType::Speed,
+                potion_stats: PotionStats {
+                    effect_duration: 5.0,
+                    effect_amount: 10,
+                },
+            },
+        },
+        ItemDefinition {
+            name: "Regeneration Potion",
+            atlas_index: 57,
+            kind: ItemKind::Potion {
+                potion_type: PotionType::Regeneration,
+                potion_stats: PotionStats {
+                    effect_duration: 12.0,
+                    effect_amount: 30,
+                },
+            },
+        },
+        ItemDefinition {
+            name: "Iron Skin Potion",
+            atlas_index: 57,
+            kind: ItemKind::Potion {
+                potion_type: PotionType::IronSkin,
+                potion_stats: PotionStats {
+                    effect_duration: 10.0,
+                    effect_amount: 5,
+                },
+            },
+        },
+        ItemDefinition {
+            name: "Fury Potion",
+            atlas_index: 57,
+            kind: ItemKind::Potion {
+                potion_type: PotionType::Fury,
+                potion_stats: PotionStats {
+                    effect_duration: 8.0,
+                    effect_amount: 30,
+                },
+            },
+        },
+        ItemDefinition {
+            name: "Ghost Potion",
+            atlas_index: 57,
+            kind: ItemKind::Potion {
+                potion_type: PotionType::Ghost,
+                potion_stats: PotionStats {
+                    effect_duration: 3.0,
+                    effect_amount: 0,
+                },
+            },
+        },
+        ItemDefinition {
+            name: "Magnet Potion",
+            atlas_index: 57,
+            kind: ItemKind::Potion {
+                potion_type: PotionType::Magnetism,
+                potion_stats: PotionStats {
+                    effect_duration: 15.0,
+                    effect_amount: 100,
+                },
+            },
+        },
+        ItemDefinition {
+            name: "Cleanse Potion",
+            atlas_index: 57,
+            kind: ItemKind::Potion {
+                potion_type: PotionType::Cleanse,
+                potion_stats: PotionStats {
+                    effect_duration: 0.0,
+                    effect_amount: 0,
+                },
+            },
+        },
+        ItemDefinition {
+            name: "Firebomb",
+            atlas_index: 57,
+            kind: ItemKind::Potion {
+                potion_type: PotionType::Firebomb,
+                potion_stats: PotionStats {
+                    effect_duration: 3.0,
+                    effect_amount: 6,
+                },
+            },
+        },
+        ItemDefinition {
+            name: "Frost Flask",
+            atlas_index: 57,
+            kind: ItemKind::Potion {
+                potion_type: PotionType::FrostFlask,
+                potion_stats: PotionStats {
+                    effect_duration: 2.0,
+                    effect_amount: 0,
+                },
+            },
+        },
+        ItemDefinition {
+            name: "Poison Flask",
+            atlas_index: 57,
+            kind: ItemKind::Potion {
+                potion_type: PotionType::PoisonFlask,
+                potion_stats: PotionStats {
+                    effect_duration: 5.0,
+                    effect_amount: 3,
+                },
+            },
+        },
+        ItemDefinition {
+            name: "Scrap Metal",
+            atlas_index: 59,
+            kind: ItemKind::Material(Color::srgb(0.6, 0.6, 0.65)),
+        },
+        ItemDefinition {
+            name: "Fabric",
+            atlas_index: 58,
+            kind: ItemKind::Material(Color::srgb(0.8, 0.65, 0.45)),
+        },
+        ItemDefinition {
+            name: "Arcane Dust",
+            atlas_index: 57,
+            kind: ItemKind::Material(Color::srgb(0.7, 0.4, 1.0)),
+        },
+    ]
+}
+
+/// Bonuses for wearing several pieces of the same set, on top of the pieces'
+/// own stats.
+pub struct ArmorSet {
+    pub name: &'static str,
+    pub bonuses: Vec<SetBonus>,
+}
+
+/// Granted while at least `pieces` items of the set are worn.
+pub struct SetBonus {
+    pub pieces: usize,
+    pub modifiers: Vec<(Stat, i32)>,
+}
+
+impl ArmorSet {
+    pub fn active_bonuses(&self, worn: usize) -> impl Iterator<Item = &(Stat, i32)> {
+        self.bonuses
+            .iter()
+            .filter(move |bonus| worn >= bonus.pieces)
+            .flat_map(|bonus| bonus.modifiers.iter())
+    }
+}
+
+pub fn armor_sets() -> Vec<ArmorSet> {
+    vec![
+        ArmorSet {
+            name: "Leather",
+            bonuses: vec![
+                SetBonus {
+                    pieces: 2,
+                    modifiers: vec![(Stat::Speed, 2)],
+                },
+                SetBonus {
+                    pieces: 3,
+                    modifiers: vec![(Stat::MaxHealth, 20), (Stat::PickupRadius, 25)],
+                },
+            ],
+        },
+        ArmorSet {
+            name: "Chain",
+            bonuses: vec![
+                SetBonus {
+                    pieces: 2,
+                    modifiers: vec![(Stat::Defense, 1)],
+                },
+                SetBonus {
+                    pieces: 3,
+                    modifiers: vec![(Stat::Defense, 2), (Stat::Thorns, 25)],
+                },
+            ],
+        },
+    ]
+}
+
+/// Spawns a hidden item from `definition`, with stats scaled by
+/// `ItemKind::scaled`.
+pub fn spawn_item(
+    commands: &mut Commands,
+    handle: &GlobalTextureAtlas,
+    definition: &ItemDefinition,
+    rarity: Rarity,
+    wave_number: u32,
+) -> Entity {
+    let sprite_bundle = SpriteBundle {
+        texture: handle.image.clone().unwrap(),
+        transform: Transform::from_scale(Vec3::splat(SPRITE_SCALE_FACTOR)),
+        visibility: Visibility::Hidden,
+        ..default()
+    };
+    let atlas = TextureAtlas {
+        layout: handle.layout.clone().unwrap(),
+        index: definition.atlas_index,
+    };
+
+    match definition.kind.scaled(rarity, wave_number) {
+        ItemKind::Gun {
+            gun_stats,
+            bullet_stats,
+            on_hit,
+        } => {
+            let mut gun = commands.spawn((
+                GunBundle {
+                    sprite_bundle,
+                    item: Item::weapon(definition.name).with_rarity(rarity),
+                    gun_stats,
+                    bullet_stats,
+                    ..default()
+                },
+                atlas,
+            ));
+            if let Some(on_hit) = on_hit {
+                gun.insert(OnHitEffect(on_hit));
+            }
+            gun.id()
+        }
+        ItemKind::Armor(armor_stats) => commands
+            .spawn((
+                ArmorBundle {
+                    armor: Armor,
+                    item: Item::armor(definition.name, armor_stats.slot).with_rarity(rarity),
+                    armor_stats,
+                    in_game_entity: InGameEntity,
+                },
+                sprite_bundle,
+                atlas,
+            ))
+            .id(),
+        ItemKind::Potion {
+            potion_type,
+            potion_stats,
+        } => commands
+            .spawn((
+                PotionBundle {
+                    sprite_bundle: SpriteBundle {
+                        sprite: Sprite {
+                            color: potion_type.tint(),
+                            ..default()
+                        },
+                        ..sprite_bundle
+                    },
+                    potion: Potion,
+                    item: Item::consumable(definition.name).with_rarity(rarity),
+                    potion_stats,
+                    potion_type,
+                    in_game_entity: InGameEntity,
+                },
+                atlas,
+            ))
+            .id(),
+        ItemKind::Material(tint) => commands
+            .spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: tint,
+                        ..default()
+                    },
+                    ..sprite_bundle
+                },
+                atlas,
+                Item::material(definition.name),
+                InGameEntity,
+            ))
+            .id(),
+    }
+}
+
+/// Spawns `amount` of `definition` for the player, split into full stacks,
+/// and marks each stack to be delivered to the inventory.
+pub fn give_item(
+    commands: &mut Commands,
+    handle: &GlobalTextureAtlas,
+    definition: &ItemDefinition,
+    rarity: Rarity,
+    wave_number: u32,
+    amount: u32,
+) {
+    let max_stack = definition.kind.category().max_stack();
+    let mut remaining = amount;
+    while remaining > 0 {
+        let stack = remaining.min(max_stack);
+        let item = spawn_item(commands, handle, definition, rarity, wave_number);
+        commands.entity(item).insert(DeliverToInventory(stack));
+        remaining -= stack;
+    }
+}
@@ -0,0 +1,76 @@
+use bevy::prelude::*;
+
+use crate::state::GameState;
+use crate::utils::clamp_position;
+use crate::{KNOCKBACK_DAMPING, KNOCKBACK_MIN_SPEED};
+
+pub struct KnockbackPlugin;
+
+#[derive(Component, Default)]
+pub struct Knockback {
+    pub velocity: Vec2,
+    pub stun: Timer,
+}
+
+#[derive(Component)]
+pub struct Mass(pub f32);
+
+#[derive(Event)]
+pub struct KnockbackEvent {
+    pub target: Entity,
+    pub impulse: Vec2,
+    pub stun: f32,
+}
+
+impl Knockback {
+    pub fn is_stunned(&self) -> bool {
+        self.stun.remaining_secs() > 0.0
+    }
+}
+
+impl Plugin for KnockbackPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<KnockbackEvent>().add_systems(
+            Update,
+            (apply_knockback_events, integrate_knockback)
+                .chain()
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+fn apply_knockback_events(
+    mut events: EventReader<KnockbackEvent>,
+    mut query: Query<(&mut Knockback, Option<&Mass>)>,
+) {
+    for event in events.read() {
+        if let Ok((mut knockback, mass)) = query.get_mut(event.target) {
+            let mass = mass.map(|m| m.0).unwrap_or(1.0).max(0.1);
+            knockback.velocity += event.impulse / mass;
+
+            let stun = event.stun / mass;
+            if stun > knockback.stun.remaining_secs() {
+                knockback.stun = Timer::from_seconds(stun, TimerMode::Once);
+            }
+        }
+    }
+}
+
+fn integrate_knockback(time: Res<Time>, mut query: Query<(&mut Knockback, &mut Transform)>) {
+    let delta = time.delta_seconds();
+    let damping = (-KNOCKBACK_DAMPING * delta).exp();
+    for (mut knockback, mut transform) in query.iter_mut() {
+        if knockback.velocity == Vec2::ZERO && !knockback.is_stunned() {
+            continue;
+        }
+
+        knockback.stun.tick(time.delta());
+        transform.translation += (knockback.velocity * delta).extend(0.0);
+        clamp_position(&mut transform.translation);
+
+        knockback.velocity *= damping;
+        if knockback.velocity.length() < KNOCKBACK_MIN_SPEED {
+            knockback.velocity = Vec2::ZERO;
+        }
+    }
+}
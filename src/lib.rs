@@ -3,17 +3,32 @@ pub mod armor;
 pub mod camera;
 pub mod collision;
 pub mod configs;
+pub mod crafting;
 pub mod dialog;
+pub mod effects;
 pub mod enemy;
 pub mod gui;
 pub mod gun;
+pub mod hotbar;
+pub mod inventory;
+pub mod inventory_screen;
+pub mod items;
+pub mod knockback;
 pub mod loot;
+pub mod pickup;
 pub mod player;
 pub mod portal;
 pub mod potion;
 pub mod resources;
+pub mod ron_asset;
+pub mod shop;
+pub mod spatial_hash;
+pub mod spatial_index;
 pub mod state;
+pub mod town;
+pub mod town_screen;
 pub mod utils;
+pub mod wallet;
 pub mod world;
 
 pub use configs::*;
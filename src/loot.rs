@@ -1,12 +1,14 @@
 use bevy::prelude::*;
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::seq::SliceRandom;
 use rand::Rng;
 
 use crate::{
-    armor::{Armor, ArmorBundle, ArmorStats},
-    gun::GunBundle,
-    potion::{Potion, PotionBundle, PotionStats, PotionType},
-    world::InGameEntity,
-    GlobalTextureAtlas, SPRITE_SCALE_FACTOR,
+    enemy::EnemyType,
+    inventory::Rarity,
+    items::{item_definitions, spawn_item, ItemKind},
+    pickup::Pickup,
+    GlobalTextureAtlas, LOOT_MAX_CHANCE, LOOT_Z, SPRITE_SCALE_FACTOR,
 };
 
 #[derive(Clone)]
@@ -19,107 +21,145 @@ pub enum LootType {
 #[derive(Clone)]
 pub struct LootDefinition {
     pub loot_type: LootType,
-    pub drop_chance: f32,
-    pub spawn_fn: fn(&mut Commands, &Transform, handle: Res<GlobalTextureAtlas>),
+    pub weight: u32,
+    pub spawn_fn: fn(&mut Commands, Vec3, &GlobalTextureAtlas, u32),
 }
 
-#[derive(Component)]
+/// Everything in `guaranteed` drops every time. On top of that, one item from
+/// `items` is picked by weight if the `drop_chance` roll succeeds.
+#[derive(Component, Clone)]
 pub struct LootPool {
+    pub drop_chance: f32,
+    pub guaranteed: Vec<LootDefinition>,
     pub items: Vec<LootDefinition>,
 }
 
 impl LootPool {
-    pub fn get_random_loot(&self) -> Option<LootDefinition> {
-        let mut rng = rand::thread_rng();
-        for item in &self.items {
-            let roll: f32 = rng.gen();
-            if roll < item.drop_chance {
-                return Some(item.clone());
-            }
+    pub fn roll(&self, chance_multiplier: f32) -> Vec<LootDefinition> {
+        let mut drops = self.guaranteed.clone();
+        let chance = (self.drop_chance * chance_multiplier).min(LOOT_MAX_CHANCE);
+        if rand::thread_rng().gen::<f32>() < chance {
+            drops.extend(self.pick_weighted().cloned());
         }
-        None
+        drops
+    }
+
+    pub fn pick_weighted(&self) -> Option<&LootDefinition> {
+        let index = WeightedIndex::new(self.items.iter().map(|item| item.weight)).ok()?;
+        self.items.get(index.sample(&mut rand::thread_rng()))
     }
 }
 
-fn spawn_gun(commands: &mut Commands, transform: &Transform, handle: Res<GlobalTextureAtlas>) {
-    commands.spawn((
-        GunBundle {
-            sprite_bundle: SpriteBundle {
-                texture: handle.image.clone().unwrap(),
-                transform: *transform,
-                ..default()
-            },
-            ..default()
-        },
-        TextureAtlas {
-            layout: handle.layout.clone().unwrap(),
-            index: 17,
-        },
-    ));
+pub fn spawn_loot(
+    commands: &mut Commands,
+    pool: &LootPool,
+    position: Vec3,
+    chance_multiplier: f32,
+    handle: &GlobalTextureAtlas,
+    wave_number: u32,
+) {
+    let mut rng = rand::thread_rng();
+    for loot in pool.roll(chance_multiplier) {
+        // Spread multiple drops out so they don't stack on one spot
+        let offset = Vec2::new(rng.gen_range(-20.0..20.0), rng.gen_range(-20.0..20.0));
+        let position = (position.truncate() + offset).extend(LOOT_Z);
+        (loot.spawn_fn)(commands, position, handle, wave_number);
+    }
 }
 
-fn spawn_armor(commands: &mut Commands, transform: &Transform, handle: Res<GlobalTextureAtlas>) {
-    commands.spawn((
-        ArmorBundle {
-            armor: Armor,
-            armor_stats: ArmorStats {
-                defense: 2,
-                durability: 20,
-            },
-            in_game_entity: InGameEntity,
-        },
-        SpriteBundle {
-            texture: handle.image.clone().unwrap(),
-            transform: *transform,
-            ..default()
-        },
-        TextureAtlas {
-            layout: handle.layout.clone().unwrap(),
-            index: 58,
-        },
-    ));
+/// Which pool an enemy archetype drops from. Elites use `strong_enemies_bundle`.
+pub fn loot_pool_for(enemy_type: &EnemyType) -> LootPool {
+    match enemy_type {
+        EnemyType::Basic | EnemyType::LeaveTrail { .. } | EnemyType::Splitter { .. } => {
+            weak_enemies_bundle()
+        }
+        EnemyType::Charge { .. }
+        | EnemyType::Shooter { .. }
+        | EnemyType::Healer { .. }
+        | EnemyType::Teleporter { .. } => medium_enemies_bundle(),
+        EnemyType::Summoner { .. } => strong_enemies_bundle(),
+    }
 }
 
-fn spawn_potion(commands: &mut Commands, transform: &Transform, handle: Res<GlobalTextureAtlas>) {
-    commands.spawn((
-        PotionBundle {
-            sprite_bundle: SpriteBundle {
-                texture: handle.image.clone().unwrap(),
-                transform: Transform::from_scale(Vec3::splat(SPRITE_SCALE_FACTOR)),
-                visibility: Visibility::Hidden,
-                ..default()
-            },
-            potion: Potion,
-            potion_stats: PotionStats {
-                effect_duration: 5.0,
-                effect_amount: 10,
-            },
-            potion_type: PotionType::Speed,
-            in_game_entity: InGameEntity,
-        },
-        SpriteBundle {
-            texture: handle.image.clone().unwrap(),
-            transform: *transform,
-            ..default()
-        },
-        TextureAtlas {
-            layout: handle.layout.clone().unwrap(),
-            index: 57,
-        },
+/// Drops a random gun from `item_definitions()`, stronger in later waves.
+fn spawn_gun(
+    commands: &mut Commands,
+    position: Vec3,
+    handle: &GlobalTextureAtlas,
+    wave_number: u32,
+) {
+    spawn_random_item(commands, position, handle, wave_number, |kind| {
+        matches!(kind, ItemKind::Gun { .. })
+    });
+}
+
+/// Drops a random armor piece from `item_definitions()`.
+fn spawn_armor(
+    commands: &mut Commands,
+    position: Vec3,
+    handle: &GlobalTextureAtlas,
+    wave_number: u32,
+) {
+    spawn_random_item(commands, position, handle, wave_number, |kind| {
+        matches!(kind, ItemKind::Armor(_))
+    });
+}
+
+/// Drops a random potion from `item_definitions()`, rarer ones more often in
+/// later waves.
+fn spawn_potion(
+    commands: &mut Commands,
+    position: Vec3,
+    handle: &GlobalTextureAtlas,
+    wave_number: u32,
+) {
+    spawn_random_item(commands, position, handle, wave_number, |kind| {
+        matches!(kind, ItemKind::Potion { .. })
+    });
+}
+
+fn spawn_random_item(
+    commands: &mut Commands,
+    position: Vec3,
+    handle: &GlobalTextureAtlas,
+    wave_number: u32,
+    is_kind: fn(&ItemKind) -> bool,
+) {
+    let definitions: Vec<_> = item_definitions()
+        .into_iter()
+        .filter(|definition| is_kind(&definition.kind))
+        .collect();
+    let Some(definition) = definitions.choose(&mut rand::thread_rng()) else {
+        return;
+    };
+    let item = spawn_item(
+        commands,
+        handle,
+        definition,
+        Rarity::roll(wave_number),
+        wave_number,
+    );
+    commands.entity(item).insert((
+        Transform::from_translation(position).with_scale(Vec3::splat(SPRITE_SCALE_FACTOR)),
+        Visibility::Visible,
+        Pickup::new(definition.kind.category()),
+        Pickup::collider(),
     ));
 }
 
 pub fn weak_enemies_bundle() -> LootPool {
     LootPool {
+        drop_chance: 0.2,
+        guaranteed: vec![],
         items: vec![
             LootDefinition {
                 loot_type: LootType::Potion,
-                drop_chance: 0.2,
+                weight: 4,
                 spawn_fn: spawn_potion,
             },
             LootDefinition {
                 loot_type: LootType::Gun,
-                drop_chance: 0.05,
+                weight: 1,
                 spawn_fn: spawn_gun,
             },
         ],
@@ -128,15 +168,17 @@ pub fn weak_enemies_bundle() -> LootPool {
 
 pub fn medium_enemies_bundle() -> LootPool {
     LootPool {
+        drop_chance: 0.35,
+        guaranteed: vec![],
         items: vec![
             LootDefinition {
                 loot_type: LootType::Potion,
-                drop_chance: 0.3,
+                weight: 3,
                 spawn_fn: spawn_potion,
             },
             LootDefinition {
                 loot_type: LootType::Armor,
-                drop_chance: 0.1,
+                weight: 1,
                 spawn_fn: spawn_armor,
             },
         ],
@@ -145,20 +187,21 @@ pub fn medium_enemies_bundle() -> LootPool {
 
 pub fn strong_enemies_bundle() -> LootPool {
     LootPool {
+        drop_chance: 0.6,
+        guaranteed: vec![LootDefinition {
+            loot_type: LootType::Potion,
+            weight: 1,
+            spawn_fn: spawn_potion,
+        }],
         items: vec![
-            LootDefinition {
-                loot_type: LootType::Potion,
-                drop_chance: 0.8,
-                spawn_fn: spawn_potion,
-            },
             LootDefinition {
                 loot_type: LootType::Gun,
-                drop_chance: 0.3,
+                weight: 3,
                 spawn_fn: spawn_gun,
             },
             LootDefinition {
                 loot_type: LootType::Armor,
-                drop_chance: 0.4,
+                weight: 4,
                 spawn_fn: spawn_armor,
             },
         ],
@@ -4,15 +4,25 @@ use bevy::asset::embedded_asset;
 use bevy::prelude::*;
 use camera::FollowCameraPlugin;
 use collision::CollisionPlugin;
+use crafting::CraftingPlugin;
 use dialog::DialogPlugin;
+use effects::EffectsPlugin;
 use enemy::EnemyPlugin;
 use fishmans_adventure::*;
 use gui::GuiPlugin;
 use gun::GunPlugin;
+use hotbar::HotbarPlugin;
+use inventory::InventoryPlugin;
+use inventory_screen::InventoryScreenPlugin;
+use knockback::KnockbackPlugin;
+use pickup::PickupPlugin;
 use player::PlayerPlugin;
 use portal::PortalPlugin;
 use potion::PotionPlugin;
+use shop::ShopPlugin;
 use state::GameState;
+use town::TownPlugin;
+use wallet::WalletPlugin;
 use world::WorldPlugin;
 
 fn main() {
@@ -40,6 +50,7 @@ fn main() {
         .add_plugins(GuiPlugin)
         .add_plugins(GunPlugin)
         .add_plugins(PlayerPlugin)
+        .add_plugins(EffectsPlugin)
         .add_plugins(AnimationPlugin)
         .add_plugins(ResourcesPlugin)
         .add_plugins(WorldPlugin)
@@ -49,6 +60,15 @@ fn main() {
         .add_plugins(PortalPlugin)
         .add_plugins(PotionPlugin)
         .add_plugins(ArmorPlugin)
+        .add_plugins(KnockbackPlugin)
+        .add_plugins(PickupPlugin)
+        .add_plugins(InventoryPlugin)
+        .add_plugins(InventoryScreenPlugin)
+        .add_plugins(HotbarPlugin)
+        .add_plugins(WalletPlugin)
+        .add_plugins(TownPlugin)
+        .add_plugins(ShopPlugin)
+        .add_plugins(CraftingPlugin)
         // .add_systems(Update, exit_game)
         .init_state::<GameState>()
         .insert_resource(Msaa::Off)
@@ -61,6 +81,13 @@ impl Plugin for EmbeddedAssetPlugin {
     fn build(&self, app: &mut App) {
         embedded_asset!(app, "../assets/assets.png");
         embedded_asset!(app, "../assets/monogram.ttf");
+        embedded_asset!(app, "../assets/dialogs/portal.dialog.ron");
+        embedded_asset!(app, "../assets/dialogs/town_exit.dialog.ron");
+        embedded_asset!(app, "../assets/dialogs/merchant.dialog.ron");
+        embedded_asset!(app, "../assets/dialogs/blacksmith.dialog.ron");
+        embedded_asset!(app, "../assets/dialogs/elder.dialog.ron");
+        embedded_asset!(app, "../assets/dialogs/workbench.dialog.ron");
+        embedded_asset!(app, "../assets/recipes/workbench.recipes.ron");
     }
 }
 
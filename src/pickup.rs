@@ -0,0 +1,246 @@
+use bevy::prelude::*;
+use bevy::time::Stopwatch;
+use bevy::utils::Duration;
+
+use crate::collision::{detect_collisions, Collider, CollisionLayer, PlayerTouchedPickup};
+use crate::effects::{ActiveEffects, Stat};
+use crate::gun::HasLifespan;
+use crate::inventory::{
+    add_to_inventory, stow_item, InventoryChangedEvent, Item, ItemCategory, PlayerInventory,
+};
+use crate::player::Player;
+use crate::state::GameState;
+use crate::wallet::Coin;
+use crate::world::InGameEntity;
+use crate::*;
+
+pub struct PickupPlugin;
+
+/// An item lying on the ground. `magnet_radius` pulls the item towards the
+/// player once they are that close.
+#[derive(Component)]
+pub struct Pickup {
+    pub category: ItemCategory,
+    pub magnet_radius: Option<f32>,
+}
+
+/// Which item categories are collected just by walking over them. Everything
+/// else needs the interact key.
+#[derive(Resource)]
+pub struct AutoPickupRules {
+    pub potions: bool,
+    pub guns: bool,
+    pub armors: bool,
+    pub materials: bool,
+}
+
+impl Default for AutoPickupRules {
+    fn default() -> Self {
+        Self {
+            potions: true,
+            guns: false,
+            armors: false,
+            materials: true,
+        }
+    }
+}
+
+impl AutoPickupRules {
+    pub fn allows(&self, category: ItemCategory) -> bool {
+        match category {
+            ItemCategory::Consumable => self.potions,
+            ItemCategory::Weapon => self.guns,
+            ItemCategory::Armor(_) => self.armors,
+            ItemCategory::Material => self.materials,
+        }
+    }
+}
+
+impl Pickup {
+    pub fn new(category: ItemCategory) -> Self {
+        let magnet_radius = matches!(category, ItemCategory::Consumable | ItemCategory::Material)
+            .then_some(PICKUP_MAGNET_RADIUS);
+        Self {
+            category,
+            magnet_radius,
+        }
+    }
+
+    pub fn collider() -> Collider {
+        Collider::circle(PICKUP_RADIUS, CollisionLayer::Pickup)
+    }
+}
+
+/// An item the player threw away. It is left alone by the magnet and by
+/// auto-pickup until the player has walked away from it once.
+#[derive(Component)]
+pub struct Dropped;
+
+/// Puts `entity` on the ground next to `position`, just out of reach so it
+/// isn't collected straight away.
+pub fn drop_item(commands: &mut Commands, entity: Entity, category: ItemCategory, position: Vec3) {
+    let direction = Vec2::from_angle(rand::random::<f32>() * std::f32::consts::TAU);
+    let position = position.truncate() + direction * PICKUP_DROP_DISTANCE;
+    commands.entity(entity).insert((
+        Transform::from_translation(position.extend(LOOT_Z))
+            .with_scale(Vec3::splat(SPRITE_SCALE_FACTOR)),
+        Visibility::Visible,
+        Pickup::new(category),
+        Pickup::collider(),
+        Dropped,
+    ));
+}
+
+impl Plugin for PickupPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AutoPickupRules>().add_systems(
+            Update,
+            (
+                release_dropped_pickups,
+                apply_pickup_magnet,
+                collect_pickups.after(detect_collisions),
+            )
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn release_dropped_pickups(
+    mut commands: Commands,
+    player_query: Query<(&Transform, Option<&ActiveEffects>), With<Player>>,
+    dropped_query: Query<(Entity, &Transform), (With<Dropped>, Without<Player>)>,
+) {
+    let Ok((player_transform, effects)) = player_query.get_single() else {
+        return;
+    };
+    let reach = effects.map_or(1.0, |effects| effects.scale(Stat::PickupRadius, 1.0));
+    let release_distance = PICKUP_MAGNET_RADIUS * reach;
+
+    for (entity, transform) in dropped_query.iter() {
+        let distance = player_transform
+            .translation
+            .truncate()
+            .distance(transform.translation.truncate());
+        if distance > release_distance {
+            commands.entity(entity).remove::<Dropped>();
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn apply_pickup_magnet(
+    player_query: Query<(&Transform, Option<&ActiveEffects>), With<Player>>,
+    mut pickup_query: Query<
+        (&mut Transform, Option<&Pickup>),
+        (
+            Or<(With<Pickup>, With<Coin>)>,
+            Without<Player>,
+            Without<Dropped>,
+        ),
+    >,
+    rules: Res<AutoPickupRules>,
+    time: Res<Time>,
+) {
+    let Ok((player_transform, effects)) = player_query.get_single() else {
+        return;
+    };
+    let player_pos = player_transform.translation.truncate();
+    let reach = effects.map_or(1.0, |effects| effects.scale(Stat::PickupRadius, 1.0));
+
+    for (mut transform, pickup) in pickup_query.iter_mut() {
+        // Coins are always pulled in
+        let magnet_radius = match pickup {
+            Some(pickup) if rules.allows(pickup.category) => pickup.magnet_radius,
+            Some(_) => None,
+            None => Some(PICKUP_MAGNET_RADIUS),
+        };
+        let Some(magnet_radius) = magnet_radius else {
+            continue;
+        };
+        let offset = player_pos - transform.translation.truncate();
+        if offset.length() <= magnet_radius * reach {
+            let step = offset.normalize_or_zero() * PICKUP_MAGNET_SPEED * time.delta_seconds();
+            transform.translation += step.clamp_length_max(offset.length()).extend(0.0);
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn collect_pickups(
+    mut commands: Commands,
+    mut player_query: Query<(&Transform, &mut PlayerInventory), With<Player>>,
+    pickup_query: Query<(&Pickup, Has<Dropped>)>,
+    mut item_query: Query<&mut Item>,
+    mut ev_touched: EventReader<PlayerTouchedPickup>,
+    mut ev_changed: EventWriter<InventoryChangedEvent>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    rules: Res<AutoPickupRules>,
+    font: Res<UiFont>,
+    time: Res<Time>,
+    mut full_message_cooldown: Local<Option<Stopwatch>>,
+) {
+    let Ok((player_transform, mut inventory)) = player_query.get_single_mut() else {
+        ev_touched.clear();
+        return;
+    };
+    if let Some(cooldown) = full_message_cooldown.as_mut() {
+        cooldown.tick(time.delta());
+    }
+
+    let interact = keyboard_input.just_pressed(KeyCode::KeyF);
+    let mut inventory_full = false;
+    let mut collected = Vec::new();
+    for touched in ev_touched.read() {
+        let pickup_entity = touched.pickup;
+        let Ok((pickup, dropped)) = pickup_query.get(pickup_entity) else {
+            continue;
+        };
+        let automatic = rules.allows(pickup.category) && !dropped;
+        if collected.contains(&pickup_entity) || (!interact && !automatic) {
+            continue;
+        }
+
+        if add_to_inventory(
+            &mut commands,
+            &mut inventory,
+            pickup_entity,
+            &mut item_query,
+            &mut ev_changed,
+        ) {
+            collected.push(pickup_entity);
+            if inventory.contains(pickup_entity) {
+                stow_item(&mut commands, pickup_entity);
+            }
+        } else {
+            inventory_full = true;
+        }
+    }
+
+    let can_show = full_message_cooldown.as_ref().map_or(true, |cooldown| {
+        cooldown.elapsed_secs() >= PICKUP_FULL_MESSAGE_COOLDOWN
+    });
+    if inventory_full && can_show {
+        spawn_inventory_full_text(&mut commands, &font.0, player_transform.translation);
+        *full_message_cooldown = Some(Stopwatch::new());
+    }
+}
+
+fn spawn_inventory_full_text(commands: &mut Commands, font: &Handle<Font>, position: Vec3) {
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section(
+                "Inventory full",
+                TextStyle {
+                    font: font.clone(),
+                    font_size: 30.0,
+                    color: Color::srgb(1.0, 0.8, 0.2),
+                },
+            ),
+            transform: Transform::from_translation(position + Vec3::new(0.0, 60.0, 30.0)),
+            ..default()
+        },
+        HasLifespan::new(Duration::from_secs(1)),
+        InGameEntity,
+    ));
+}
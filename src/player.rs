@@ -1,11 +1,12 @@
 use std::time::Duration;
 
-use armor::{Armor, ArmorStats};
 use bevy::math::vec3;
 use bevy::prelude::*;
 use bevy::time::Stopwatch;
-use enemy::Collider;
+use dialog::no_active_dialog;
+use effects::{Effect, EffectAction, EffectSource, Stat};
 use gun::HasLifespan;
+use knockback::Knockback;
 use utils::{calculate_defense_increase, calculate_health_increase, safe_subtract};
 use world::InGameEntity;
 
@@ -22,20 +23,9 @@ pub struct Health(pub u32);
 pub struct Speed(pub u32);
 #[derive(Component)]
 pub struct Defense(pub u32);
-#[derive(Component)]
-pub struct PlayerInventory {
-    pub guns: Vec<Entity>,
-    pub health_potions: Vec<Entity>,
-    pub speed_potions: Vec<Entity>,
-    pub armors: Vec<Entity>,
-    pub active_gun_index: usize,
-    pub active_armor_index: usize,
-}
 
 #[derive(Component)]
 pub struct InvincibilityEffect(pub Stopwatch, pub f32);
-#[derive(Component)]
-pub struct AccelerationEffect(pub Stopwatch, pub f32, pub u32);
 
 #[derive(Component, Default, Debug)]
 pub enum PlayerState {
@@ -61,102 +51,70 @@ impl Plugin for PlayerPlugin {
                 Update,
                 (
                     handle_player_death,
-                    handle_player_input,
                     handle_player_damaged_events,
                     handle_invincibility_effect,
-                    handle_acceleration_effect,
                     handle_leveling_up,
                 )
                     .run_if(in_state(GameState::InGame)),
+            )
+            .add_systems(
+                Update,
+                handle_player_input
+                    .run_if(in_state(GameState::InGame).or_else(in_state(GameState::Town)))
+                    .run_if(no_active_dialog),
             );
     }
 }
 
 fn handle_player_damaged_events(
     mut commands: Commands,
-    mut player_query: Query<
-        (
-            &mut Health,
-            &PlayerState,
-            &Defense,
-            &mut PlayerInventory,
-            &Transform,
-        ),
-        With<Player>,
-    >,
-    mut armor_query: Query<(&mut ArmorStats, Entity), With<Armor>>,
+    mut player_query: Query<(&mut Health, &PlayerState, &Defense, &Transform), With<Player>>,
     mut events: EventReader<PlayerDamagedEvent>,
     font: Res<UiFont>,
 ) {
     if player_query.is_empty() {
         return;
     }
-    let (mut health, _player_state, player_defense, mut inventory, player_transform) =
-        player_query.single_mut();
+    let (mut health, _player_state, player_defense, player_transform) = player_query.single_mut();
 
     for event in events.read() {
-        if health.0 > 0 {
-            let mut total_defense = player_defense.0;
-
-            if let Some(active_armor_entity) = inventory.armors.get(inventory.active_armor_index) {
-                if let Ok((mut armor_stats, armor_entity)) =
-                    armor_query.get_mut(*active_armor_entity)
-                {
-                    total_defense += armor_stats.defense;
-
-                    let damage_after_defense = safe_subtract(event.damage, total_defense);
-                    health.0 = safe_subtract(health.0, damage_after_defense);
-
-                    armor_stats.durability =
-                        safe_subtract(armor_stats.durability, damage_after_defense);
-
-                    if armor_stats.durability <= 0 {
-                        commands.entity(armor_entity).despawn();
-                        let armor_to_remove = inventory.active_armor_index;
-                        inventory.armors.remove(armor_to_remove);
-                        if inventory.active_armor_index >= inventory.armors.len() {
-                            inventory.active_armor_index = 0;
-                        }
-                    }
-                    if damage_after_defense > 0 {
-                        spawn_damage_text(
-                            &mut commands,
-                            &font.0,
-                            player_transform.translation,
-                            damage_after_defense,
-                        );
-                    }
-                }
-            } else {
-                let damage_after_defense = safe_subtract(event.damage, player_defense.0);
-                health.0 = safe_subtract(health.0, damage_after_defense);
-                if damage_after_defense > 0 {
-                    spawn_damage_text(
-                        &mut commands,
-                        &font.0,
-                        player_transform.translation,
-                        damage_after_defense,
-                    );
-                }
-            }
+        if health.0 == 0 {
+            continue;
+        }
+        // Worn armor already counts towards `Defense` through its effect and
+        // wears down separately in `armor.rs`
+        let damage_after_defense = safe_subtract(event.damage, player_defense.0);
+        health.0 = safe_subtract(health.0, damage_after_defense);
+        if damage_after_defense > 0 {
+            spawn_damage_text(
+                &mut commands,
+                &font.0,
+                player_transform.translation,
+                damage_after_defense,
+            );
         }
     }
 }
 
 fn handle_leveling_up(
     mut event_reader: EventReader<PlayerLevelingUpEvent>,
-    mut player_query: Query<(&mut Health, &mut Defense), With<Player>>,
+    mut player_query: Query<(&mut Health, Entity), With<Player>>,
+    mut ev_effect: EventWriter<EffectAction>,
 ) {
     if player_query.is_empty() {
         return;
     }
 
-    let (mut health, mut defense) = player_query.single_mut();
+    let (mut health, entity) = player_query.single_mut();
 
     for event in event_reader.read() {
-        let level = event.new_level as u32;
+        let level = event.new_level;
         health.0 += calculate_health_increase(level);
-        defense.0 += calculate_defense_increase(level);
+        ev_effect.send(EffectAction::Apply {
+            target: entity,
+            effect: Effect::new(EffectSource::LevelUp(level), None)
+                .with_modifier(Stat::Defense, calculate_defense_increase(level) as i32),
+        });
     }
 }
 
@@ -177,7 +135,6 @@ fn spawn_damage_text(commands: &mut Commands, font: &Handle<Font>, position: Vec
             },
             ..default()
         },
-        Collider { radius: 5 },
         HasLifespan::new(Duration::from_secs(1)),
         InGameEntity,
     ));
@@ -211,32 +168,20 @@ fn handle_invincibility_effect(
     }
     invincibility_effect.0.tick(time.delta());
 }
-fn handle_acceleration_effect(
-    time: Res<Time>,
-    mut commands: Commands,
-    mut player_query: Query<(&mut AccelerationEffect, &mut Speed, Entity), With<Player>>,
-) {
-    if player_query.is_empty() {
-        return;
-    }
-    let (mut acceleration_effect, mut speed, entity) = player_query.single_mut();
-
-    if acceleration_effect.0.elapsed_secs() >= acceleration_effect.1 {
-        commands.entity(entity).remove::<AccelerationEffect>();
-        speed.0 -= acceleration_effect.2;
-    }
-    acceleration_effect.0.tick(time.delta());
-}
 
 pub fn handle_player_input(
-    mut player_query: Query<(&mut Transform, &mut PlayerState, &Speed), With<Player>>,
+    mut player_query: Query<(&mut Transform, &mut PlayerState, &Speed, &Knockback), With<Player>>,
     keyboard_input: Res<ButtonInput<KeyCode>>,
 ) {
     if player_query.is_empty() {
         return;
     }
 
-    let (mut transform, mut player_state, speed) = player_query.single_mut();
+    let (mut transform, mut player_state, speed, knockback) = player_query.single_mut();
+    if knockback.is_stunned() {
+        *player_state = PlayerState::Idle;
+        return;
+    }
     let w_key = keyboard_input.pressed(KeyCode::KeyW) || keyboard_input.pressed(KeyCode::ArrowUp);
     let a_key = keyboard_input.pressed(KeyCode::KeyA) || keyboard_input.pressed(KeyCode::ArrowLeft);
     let s_key = keyboard_input.pressed(KeyCode::KeyS) || keyboard_input.pressed(KeyCode::ArrowDown);
@@ -1,8 +1,10 @@
-use crate::dialog::{ActiveDialog, DialogType, ShowDialogEvent};
+use crate::collision::{detect_collisions, Collider, CollisionLayer, PlayerNearInteractable};
+use crate::dialog::{ActiveDialog, ShowDialogEvent};
 use crate::player::Player;
 use crate::resources::Wave;
 use crate::state::GameState;
 use crate::world::InGameEntity;
+use crate::PORTAL_INTERACT_RADIUS;
 use bevy::prelude::*;
 
 pub struct PortalPlugin;
@@ -14,7 +16,8 @@ impl Plugin for PortalPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
             Update,
-            (spawn_portal, portal_interaction).run_if(in_state(GameState::InGame)),
+            (spawn_portal, portal_interaction.after(detect_collisions))
+                .run_if(in_state(GameState::InGame)),
         );
     }
 }
@@ -39,6 +42,7 @@ fn spawn_portal(
                     ..default()
                 },
                 Portal,
+                Collider::circle(PORTAL_INTERACT_RADIUS, CollisionLayer::Interactable),
                 InGameEntity,
             ));
 
@@ -48,24 +52,18 @@ fn spawn_portal(
 }
 
 fn portal_interaction(
-    player_query: Query<&Transform, With<Player>>,
-    portal_query: Query<&Transform, With<Portal>>,
+    portal_query: Query<(), With<Portal>>,
+    mut ev_near: EventReader<PlayerNearInteractable>,
     keyboard_input: Res<ButtonInput<KeyCode>>,
     mut ev_show_dialog: EventWriter<ShowDialogEvent>,
     active_dialog: Res<ActiveDialog>,
 ) {
-    if let Ok(player_transform) = player_query.get_single() {
-        for portal_transform in portal_query.iter() {
-            let distance = player_transform
-                .translation
-                .distance(portal_transform.translation);
+    let mut near_portal = false;
+    for near in ev_near.read() {
+        near_portal |= portal_query.contains(near.interactable);
+    }
 
-            if distance < 60.0
-                && keyboard_input.just_pressed(KeyCode::KeyX)
-                && active_dialog.0.is_none()
-            {
-                ev_show_dialog.send(ShowDialogEvent(DialogType::Portal));
-            }
-        }
+    if near_portal && keyboard_input.just_pressed(KeyCode::KeyX) && active_dialog.0.is_none() {
+        ev_show_dialog.send(ShowDialogEvent::new("portal"));
     }
 }
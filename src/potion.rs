@@ -1,21 +1,36 @@
 use bevy::{prelude::*, time::Stopwatch};
 
 use crate::{
-    player::{AccelerationEffect, Health, Player, PlayerInventory, Speed},
+    effects::{Effect, EffectAction, EffectSource, StackingRule, Stat, TickEffect},
+    enemy::{spawn_status_splash, StatusEffect, StatusKind},
+    hotbar::{handle_hotbar_input, hotbar_slot, HotbarUseEvent},
+    inventory::{consume_one, InventoryChangedEvent, Item, PlayerInventory},
+    player::{Health, InvincibilityEffect, Player},
     state::GameState,
     world::InGameEntity,
+    CursorPosition, REGENERATION_MAX_STACKS, STATUS_TICK_INTERVAL, THROWABLE_RADIUS,
+    THROWABLE_RANGE,
 };
 
 #[derive(Component)]
 pub struct Potion;
 
-#[derive(Component)]
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
 pub enum PotionType {
     Health,
     Speed,
+    Regeneration,
+    IronSkin,
+    Fury,
+    Ghost,
+    Magnetism,
+    Cleanse,
+    Firebomb,
+    FrostFlask,
+    PoisonFlask,
 }
 
-#[derive(Component)]
+#[derive(Component, Clone)]
 pub struct PotionStats {
     pub effect_duration: f32,
     pub effect_amount: u32,
@@ -24,6 +39,7 @@ pub struct PotionStats {
 #[derive(Bundle)]
 pub struct PotionBundle {
     pub potion: Potion,
+    pub item: Item,
     pub potion_stats: PotionStats,
     pub potion_type: PotionType,
     pub in_game_entity: InGameEntity,
@@ -32,48 +48,190 @@ pub struct PotionBundle {
 
 pub struct PotionPlugin;
 
+impl PotionType {
+    pub fn tint(&self) -> Color {
+        match self {
+            PotionType::Health => Color::srgb(0.9, 0.2, 0.25),
+            PotionType::Speed => Color::srgb(0.3, 0.8, 1.0),
+            PotionType::Regeneration => Color::srgb(1.0, 0.5, 0.6),
+            PotionType::IronSkin => Color::srgb(0.65, 0.65, 0.7),
+            PotionType::Fury => Color::srgb(1.0, 0.45, 0.1),
+            PotionType::Ghost => Color::srgb(0.85, 0.85, 1.0),
+            PotionType::Magnetism => Color::srgb(0.7, 0.3, 1.0),
+            PotionType::Cleanse => Color::srgb(0.4, 1.0, 0.8),
+            PotionType::Firebomb => StatusKind::Burn.tint(),
+            PotionType::FrostFlask => StatusKind::Freeze.tint(),
+            PotionType::PoisonFlask => StatusKind::Poison.tint(),
+        }
+    }
+
+    /// What a merchant charges before rarity.
+    pub fn base_price(&self) -> u32 {
+        match self {
+            PotionType::Health => 10,
+            PotionType::Speed => 12,
+            PotionType::Regeneration => 25,
+            PotionType::IronSkin => 20,
+            PotionType::Fury => 30,
+            PotionType::Ghost => 25,
+            PotionType::Magnetism => 10,
+            PotionType::Cleanse => 15,
+            PotionType::Firebomb => 20,
+            PotionType::FrostFlask => 25,
+            PotionType::PoisonFlask => 20,
+        }
+    }
+
+    /// The status a thrown flask applies to enemies where it lands.
+    pub fn thrown_status(&self) -> Option<StatusKind> {
+        match self {
+            PotionType::Firebomb => Some(StatusKind::Burn),
+            PotionType::FrostFlask => Some(StatusKind::Freeze),
+            PotionType::PoisonFlask => Some(StatusKind::Poison),
+            _ => None,
+        }
+    }
+
+    pub fn description(&self, stats: &PotionStats) -> String {
+        let (amount, duration) = (stats.effect_amount, stats.effect_duration);
+        match self {
+            PotionType::Health => format!("Heals {}", amount),
+            PotionType::Regeneration => format!("Heals {} over {:.1}s", amount, duration),
+            PotionType::Speed => format!("+{} speed for {:.1}s", amount, duration),
+            PotionType::IronSkin => format!("+{} defense for {:.1}s", amount, duration),
+            PotionType::Fury => format!("+{}% fire rate and damage for {:.1}s", amount, duration),
+            PotionType::Ghost => format!("Invincible for {:.1}s", duration),
+            PotionType::Magnetism => format!("+{}% pickup radius for {:.1}s", amount, duration),
+            PotionType::Cleanse => "Removes all debuffs".to_string(),
+            PotionType::Firebomb => format!(
+                "Thrown, burns for {} per tick over {:.1}s",
+                amount, duration
+            ),
+            PotionType::FrostFlask => format!("Thrown, freezes for {:.1}s", duration),
+            PotionType::PoisonFlask => format!(
+                "Thrown, poisons for {} per tick over {:.1}s",
+                amount, duration
+            ),
+        }
+    }
+}
+
 impl Plugin for PotionPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
             Update,
-            apply_potion_effects.run_if(in_state(GameState::InGame)),
+            apply_potion_effects
+                .after(handle_hotbar_input)
+                .run_if(in_state(GameState::InGame)),
         );
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn apply_potion_effects(
     mut commands: Commands,
-    mut player_query: Query<(&mut Health, &mut PlayerInventory, Entity, &mut Speed), With<Player>>,
-    potion_query: Query<(Entity, &PotionStats), With<Potion>>,
-    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut player_query: Query<(&mut PlayerInventory, &mut Health, &Transform, Entity), With<Player>>,
+    potion_query: Query<(&PotionStats, &PotionType), With<Potion>>,
+    mut item_query: Query<&mut Item>,
+    mut ev_use: EventReader<HotbarUseEvent>,
+    mut ev_inventory_changed: EventWriter<InventoryChangedEvent>,
+    mut ev_effect: EventWriter<EffectAction>,
+    cursor_position: Res<CursorPosition>,
 ) {
-    // Ensure there's only one player entity
-    let (mut health, mut player_inventory, entity, mut speed) = player_query.single_mut();
-    // Assuming health and speed effects are keyed to specific keys
-    if keyboard_input.just_pressed(KeyCode::Digit1) {
-        if let Some(health_potion_entity) = player_inventory.health_potions.get(0) {
-            if let Ok((potion_entity, potion_stats)) = potion_query.get(*health_potion_entity) {
-                // Apply health effect
+    let Ok((mut player_inventory, mut health, transform, entity)) = player_query.get_single_mut()
+    else {
+        ev_use.clear();
+        return;
+    };
+
+    for used in ev_use.read() {
+        let Ok((potion_stats, potion_type)) = potion_query.get(used.item) else {
+            continue;
+        };
+
+        match potion_type {
+            PotionType::Health => {
                 health.0 += potion_stats.effect_amount;
-                commands.entity(potion_entity).despawn(); // Despawn the potion entity
-                player_inventory.health_potions.remove(0);
             }
-        }
-    }
-
-    if keyboard_input.just_pressed(KeyCode::Digit2) {
-        if let Some(speed_potion_entity) = player_inventory.speed_potions.get(0) {
-            if let Ok((potion_entity, potion_stats)) = potion_query.get(*speed_potion_entity) {
-                // Apply speed potion effect
-                commands.entity(entity).insert(AccelerationEffect(
+            PotionType::Ghost => {
+                commands.entity(entity).insert(InvincibilityEffect(
                     Stopwatch::new(),
                     potion_stats.effect_duration,
-                    potion_stats.effect_amount,
                 ));
-                speed.0 += potion_stats.effect_amount;
-                commands.entity(potion_entity).despawn(); // Despawn the potion entity
-                player_inventory.speed_potions.remove(0);
             }
+            PotionType::Cleanse => {
+                ev_effect.send(EffectAction::Cleanse { target: entity });
+            }
+            _ => {}
         }
+        if let Some(kind) = potion_type.thrown_status() {
+            let player_pos = transform.translation.truncate();
+            let target = cursor_position.0.map_or(player_pos, |cursor| {
+                player_pos + (cursor - player_pos).clamp_length_max(THROWABLE_RANGE)
+            });
+            let status = StatusEffect::new(
+                kind,
+                potion_stats.effect_duration,
+                potion_stats.effect_amount as f32,
+            );
+            spawn_status_splash(&mut commands, target, status, THROWABLE_RADIUS);
+        }
+        if let Some(effect) = potion_effect(*potion_type, potion_stats) {
+            ev_effect.send(EffectAction::Apply {
+                target: entity,
+                effect,
+            });
+        }
+        consume_one(
+            &mut commands,
+            &mut player_inventory,
+            hotbar_slot(used.slot),
+            &mut item_query,
+            &mut ev_inventory_changed,
+        );
     }
 }
+
+/// The lasting part of a potion, if any. Stat buffs keep the strongest
+/// dose, regeneration stacks; healing, cleanse and thrown flasks act once
+/// and have no effect to show.
+pub fn potion_effect(potion_type: PotionType, stats: &PotionStats) -> Option<Effect> {
+    let effect = Effect::new(
+        EffectSource::Potion(potion_type),
+        Some(stats.effect_duration),
+    );
+    let amount = stats.effect_amount as i32;
+    let effect = match potion_type {
+        PotionType::Regeneration => {
+            let ticks = (stats.effect_duration / STATUS_TICK_INTERVAL)
+                .floor()
+                .max(1.0);
+            let per_tick = (stats.effect_amount as f32 / ticks).ceil() as u32;
+            effect
+                .with_tick(TickEffect::Heal(per_tick))
+                .with_stacking(StackingRule::Stack(REGENERATION_MAX_STACKS))
+        }
+        PotionType::Speed => effect
+            .with_modifier(Stat::Speed, amount)
+            .with_stacking(StackingRule::KeepStrongest),
+        PotionType::IronSkin => effect
+            .with_modifier(Stat::Defense, amount)
+            .with_stacking(StackingRule::KeepStrongest),
+        PotionType::Fury => effect
+            .with_modifier(Stat::FireRate, amount)
+            .with_modifier(Stat::Damage, amount)
+            .with_stacking(StackingRule::KeepStrongest),
+        PotionType::Magnetism => effect
+            .with_modifier(Stat::PickupRadius, amount)
+            .with_stacking(StackingRule::KeepStrongest),
+        // Invincibility itself comes from `InvincibilityEffect`, this only
+        // shows the timer
+        PotionType::Ghost => effect,
+        PotionType::Health
+        | PotionType::Cleanse
+        | PotionType::Firebomb
+        | PotionType::FrostFlask
+        | PotionType::PoisonFlask => return None,
+    };
+    Some(effect)
+}
@@ -6,7 +6,7 @@ use crate::*;
 
 pub struct ResourcesPlugin;
 
-#[derive(Resource)]
+#[derive(Resource, Default)]
 pub struct GlobalTextureAtlas {
     pub layout: Option<Handle<TextureAtlasLayout>>,
     pub image: Option<Handle<Image>>,
@@ -16,6 +16,24 @@ pub struct CursorPosition(pub Option<Vec2>);
 #[derive(Resource)]
 pub struct UiFont(pub Handle<Font>);
 
+#[derive(Resource)]
+pub struct Settings {
+    pub show_enemy_health_bars: bool,
+    pub show_damage_numbers: bool,
+    /// Fraction of the coins kept when a new run starts.
+    pub coin_carry_over: f32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            show_enemy_health_bars: true,
+            show_damage_numbers: true,
+            coin_carry_over: COIN_CARRY_OVER,
+        }
+    }
+}
+
 #[derive(Resource)]
 pub struct Wave {
     pub number: u32,
@@ -90,10 +108,12 @@ impl Plugin for ResourcesPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(GlobalTextureAtlas::default())
             .insert_resource(CursorPosition(None))
+            .init_resource::<Settings>()
             .add_systems(OnEnter(GameState::Loading), load_assets)
             .add_systems(
                 Update,
-                update_cursor_position.run_if(in_state(GameState::InGame)),
+                update_cursor_position
+                    .run_if(in_state(GameState::InGame).or_else(in_state(GameState::Town))),
             );
     }
 }
@@ -136,12 +156,3 @@ fn update_cursor_position(
         .and_then(|cursor| camera.viewport_to_world(camera_transform, cursor))
         .map(|ray| ray.origin.truncate());
 }
-
-impl Default for GlobalTextureAtlas {
-    fn default() -> Self {
-        Self {
-            layout: None,
-            image: None,
-        }
-    }
-}
@@ -0,0 +1,73 @@
+use std::fmt;
+use std::marker::PhantomData;
+
+use bevy::asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext};
+use bevy::prelude::*;
+use serde::de::DeserializeOwned;
+
+/// An asset stored as a RON file, loaded with `RonAssetLoader`.
+pub trait RonAsset: Asset + DeserializeOwned {
+    /// What the asset is called in load errors, e.g. "dialog script".
+    const NAME: &'static str;
+    const EXTENSIONS: &'static [&'static str];
+
+    /// Checks the parsed asset, so mistakes such as references to unknown
+    /// entries fail at load time rather than in the middle of a game.
+    fn validate(&self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+pub struct RonAssetLoader<A>(PhantomData<fn() -> A>);
+
+impl<A> Default for RonAssetLoader<A> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+#[derive(Debug)]
+pub enum RonAssetError {
+    Io(&'static str, std::io::Error),
+    Ron(&'static str, ron::error::SpannedError),
+    Invalid(String),
+}
+
+impl fmt::Display for RonAssetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RonAssetError::Io(name, err) => write!(f, "could not read {name}: {err}"),
+            RonAssetError::Ron(name, err) => write!(f, "could not parse {name}: {err}"),
+            RonAssetError::Invalid(reason) => write!(f, "{reason}"),
+        }
+    }
+}
+
+impl std::error::Error for RonAssetError {}
+
+impl<A: RonAsset> AssetLoader for RonAssetLoader<A> {
+    type Asset = A;
+    type Settings = ();
+    type Error = RonAssetError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<A, Self::Error> {
+        let mut bytes = Vec::new();
+        reader
+            .read_to_end(&mut bytes)
+            .await
+            .map_err(|err| RonAssetError::Io(A::NAME, err))?;
+        let asset: A =
+            ron::de::from_bytes(&bytes).map_err(|err| RonAssetError::Ron(A::NAME, err))?;
+        asset.validate().map_err(RonAssetError::Invalid)?;
+        Ok(asset)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        A::EXTENSIONS
+    }
+}
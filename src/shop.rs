@@ -0,0 +1,379 @@
+use bevy::prelude::*;
+use rand::seq::SliceRandom;
+
+use crate::armor::ArmorStats;
+use crate::dialog::{close_dialog, ActiveDialog, DialogAction, DialogActionEvent};
+use crate::gun::{BulletStats, GunStats, WeaponSummary};
+use crate::inventory::{
+    add_to_inventory, consume_one, InventoryChangedEvent, Item, PlayerInventory, Rarity,
+};
+use crate::items::{item_definitions, spawn_item, ItemKind};
+use crate::player::Player;
+use crate::potion::{PotionStats, PotionType};
+use crate::state::GameState;
+use crate::town::TownEntity;
+use crate::town_screen::{
+    close_town_screen, list_sections, navigate_town_screen, spawn_town_screen, town_screen_closed,
+    TownScreenMode,
+};
+use crate::wallet::{CoinReason, Wallet, WalletAction};
+use crate::*;
+
+pub struct ShopPlugin;
+
+/// A town NPC that trades. `stock` holds hidden item entities that move into
+/// the player's inventory when bought.
+#[derive(Component)]
+pub struct Merchant {
+    pub stock: Vec<Entity>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum ShopMode {
+    #[default]
+    Buy,
+    Sell,
+}
+
+impl TownScreenMode for ShopMode {
+    fn other(self) -> Self {
+        match self {
+            ShopMode::Buy => ShopMode::Sell,
+            ShopMode::Sell => ShopMode::Buy,
+        }
+    }
+}
+
+/// The merchant being traded with, if any. `selected` indexes the stock when
+/// buying and `PlayerInventory::bag_slots()` when selling, so equipped items
+/// are never for sale.
+#[derive(Resource, Default)]
+pub struct ShopState {
+    pub merchant: Option<Entity>,
+    pub mode: ShopMode,
+    pub selected: usize,
+    pub message: String,
+}
+
+#[derive(Component)]
+struct ShopScreen;
+#[derive(Component)]
+struct ShopTitle;
+#[derive(Component)]
+struct ShopList(ShopMode);
+
+type ItemStats<'a> = (
+    Option<&'a GunStats>,
+    Option<&'a BulletStats>,
+    Option<&'a ArmorStats>,
+    Option<&'a PotionStats>,
+    Option<&'a PotionType>,
+);
+
+impl Plugin for ShopPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ShopState>().add_systems(
+            Update,
+            (
+                (handle_shop_input, update_shop_display).run_if(shop_open),
+                open_shop,
+            )
+                .chain()
+                .after(close_dialog)
+                .run_if(in_state(GameState::Town)),
+        );
+    }
+}
+
+fn shop_open(shop: Res<ShopState>) -> bool {
+    shop.merchant.is_some()
+}
+
+/// What a merchant charges for one of `item`. Selling gets `SHOP_SELL_RATIO`
+/// of this back.
+pub fn item_price(item: &Item, (gun, bullet, armor, _, potion_type): ItemStats<'_>) -> u32 {
+    let mut base = 0.0;
+    if let (Some(gun), Some(bullet)) = (gun, bullet) {
+        let summary = WeaponSummary::new(gun, bullet);
+        base += summary.dps * 0.02 + summary.range * 0.5;
+    }
+    if let Some(armor) = armor {
+        base += armor.defense as f32 * 8.0 + armor.durability as f32 * 0.5;
+        base += armor
+            .modifiers
+            .iter()
+            .map(|(_, amount)| amount.abs() as f32 * 2.0)
+            .sum::<f32>();
+    }
+    if let Some(potion_type) = potion_type {
+        base += potion_type.base_price() as f32;
+    }
+    ((base * item.rarity.price_multiplier()).round() as u32).max(1)
+}
+
+pub fn sell_price(item: &Item, stats: ItemStats<'_>) -> u32 {
+    ((item_price(item, stats) as f32 * SHOP_SELL_RATIO).floor() as u32).max(1)
+}
+
+/// A random pick of potions and gear, scaled to the current wave.
+pub fn generate_stock(
+    commands: &mut Commands,
+    handle: &GlobalTextureAtlas,
+    wave_number: u32,
+) -> Vec<Entity> {
+    let (potions, gear): (Vec<_>, Vec<_>) = item_definitions()
+        .into_iter()
+        .filter(|definition| !matches!(definition.kind, ItemKind::Material(_)))
+        .partition(|definition| matches!(definition.kind, ItemKind::Potion { .. }));
+
+    let mut rng = rand::thread_rng();
+    let mut stock = Vec::new();
+    for i in 0..SHOP_STOCK_SIZE {
+        let pool = if i < SHOP_POTION_COUNT {
+            &potions
+        } else {
+            &gear
+        };
+        if let Some(definition) = pool.choose(&mut rng) {
+            stock.push(spawn_item(
+                commands,
+                handle,
+                definition,
+                Rarity::roll(wave_number),
+                wave_number,
+            ));
+        }
+    }
+    for entity in stock.iter() {
+        commands.entity(*entity).insert(TownEntity);
+    }
+    stock
+}
+
+/// Opens the trade screen when a merchant's dialog asks for it.
+fn open_shop(
+    mut commands: Commands,
+    mut ev_action: EventReader<DialogActionEvent>,
+    merchant_query: Query<(), With<Merchant>>,
+    mut active_dialog: ResMut<ActiveDialog>,
+    mut shop: ResMut<ShopState>,
+    font: Res<UiFont>,
+) {
+    for event in ev_action.read() {
+        let (DialogAction::OpenShop, Some(merchant)) = (&event.action, event.npc) else {
+            continue;
+        };
+        if active_dialog.0.is_some() || !merchant_query.contains(merchant) {
+            continue;
+        }
+        *shop = ShopState {
+            merchant: Some(merchant),
+            ..default()
+        };
+        active_dialog.0 = Some(spawn_shop_screen(&mut commands, &font.0));
+    }
+}
+
+fn spawn_shop_screen(commands: &mut Commands, font: &Handle<Font>) -> Entity {
+    let layout = spawn_town_screen(
+        commands,
+        font,
+        360.0,
+        2,
+        "Up/Down: select  Left/Right: buy/sell  Enter: confirm  Esc: leave",
+    );
+    commands.entity(layout.root).insert(ShopScreen);
+    commands.entity(layout.title).insert(ShopTitle);
+    for (entity, mode) in layout
+        .columns
+        .into_iter()
+        .zip([ShopMode::Buy, ShopMode::Sell])
+    {
+        commands.entity(entity).insert(ShopList(mode));
+    }
+    layout.root
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn handle_shop_input(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut shop: ResMut<ShopState>,
+    mut active_dialog: ResMut<ActiveDialog>,
+    mut merchant_query: Query<&mut Merchant>,
+    mut player_query: Query<&mut PlayerInventory, With<Player>>,
+    mut item_query: Query<&mut Item>,
+    stats_query: Query<ItemStats>,
+    wallet: Res<Wallet>,
+    mut ev_wallet: EventWriter<WalletAction>,
+    mut ev_changed: EventWriter<InventoryChangedEvent>,
+) {
+    if town_screen_closed(&keyboard_input) {
+        close_town_screen(&mut commands, &mut *shop, &mut active_dialog);
+        return;
+    }
+    let (Some(mut merchant), Ok(mut inventory)) = (
+        shop.merchant
+            .and_then(|entity| merchant_query.get_mut(entity).ok()),
+        player_query.get_single_mut(),
+    ) else {
+        close_town_screen(&mut commands, &mut *shop, &mut active_dialog);
+        return;
+    };
+
+    let shop = &mut *shop;
+    let Some(selected) = navigate_town_screen(
+        &keyboard_input,
+        &mut shop.mode,
+        &mut shop.selected,
+        |mode| match mode {
+            ShopMode::Buy => merchant.stock.len(),
+            ShopMode::Sell => inventory.bag_slots().count(),
+        },
+    ) else {
+        return;
+    };
+    match shop.mode {
+        ShopMode::Buy => {
+            let entity = merchant.stock[selected];
+            let (Ok(item), Ok(stats)) = (item_query.get(entity), stats_query.get(entity)) else {
+                return;
+            };
+            let price = item_price(item, stats);
+            if wallet.coins < price {
+                shop.message = "Not enough coins".to_string();
+                return;
+            }
+            commands.entity(entity).remove::<TownEntity>();
+            if !add_to_inventory(
+                &mut commands,
+                &mut inventory,
+                entity,
+                &mut item_query,
+                &mut ev_changed,
+            ) {
+                commands.entity(entity).insert(TownEntity);
+                shop.message = "Inventory full".to_string();
+                return;
+            }
+            merchant.stock.remove(selected);
+            ev_wallet.send(WalletAction::Spend {
+                amount: price,
+                reason: CoinReason::Purchase,
+            });
+            shop.message = format!(
+                "Bought {} for {} coins",
+                item_name(&item_query, entity),
+                price
+            );
+        }
+        ShopMode::Sell => {
+            let Some((slot, entity)) = inventory.bag_slots().nth(selected) else {
+                return;
+            };
+            let (Ok(item), Ok(stats)) = (item_query.get(entity), stats_query.get(entity)) else {
+                return;
+            };
+            let price = sell_price(item, stats);
+            shop.message = format!("Sold {} for {} coins", item.name, price);
+            consume_one(
+                &mut commands,
+                &mut inventory,
+                slot,
+                &mut item_query,
+                &mut ev_changed,
+            );
+            ev_wallet.send(WalletAction::Earn {
+                amount: price,
+                reason: CoinReason::Sale,
+            });
+        }
+    }
+}
+
+fn item_name(item_query: &Query<&mut Item>, entity: Entity) -> String {
+    item_query
+        .get(entity)
+        .map(|item| item.name.clone())
+        .unwrap_or_default()
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_shop_display(
+    shop: Res<ShopState>,
+    wallet: Res<Wallet>,
+    merchant_query: Query<&Merchant>,
+    player_query: Query<&PlayerInventory, With<Player>>,
+    item_query: Query<(&Item, ItemStats)>,
+    mut title_query: Query<&mut Text, (With<ShopTitle>, Without<ShopList>)>,
+    mut list_query: Query<(&mut Text, &ShopList), Without<ShopTitle>>,
+    font: Res<UiFont>,
+) {
+    let (Some(merchant), Ok(inventory)) = (
+        shop.merchant
+            .and_then(|entity| merchant_query.get(entity).ok()),
+        player_query.get_single(),
+    ) else {
+        return;
+    };
+    if let Ok(mut title) = title_query.get_single_mut() {
+        title.sections[0].value = format!("Merchant - Coins: {}\n{}", wallet.coins, shop.message);
+    }
+
+    for (mut text, list) in list_query.iter_mut() {
+        let entries = match list.0 {
+            ShopMode::Buy => merchant
+                .stock
+                .iter()
+                .filter_map(|entity| item_query.get(*entity).ok())
+                .map(|(item, stats)| {
+                    format!(
+                        "{} ({:?}){} - {}c",
+                        item.name,
+                        item.rarity,
+                        stat_summary(stats),
+                        item_price(item, stats)
+                    )
+                })
+                .collect::<Vec<_>>(),
+            ShopMode::Sell => inventory
+                .bag_slots()
+                .filter_map(|(_, entity)| item_query.get(entity).ok())
+                .map(|(item, stats)| {
+                    format!(
+                        "{} x{}{} - {}c",
+                        item.name,
+                        item.stack,
+                        stat_summary(stats),
+                        sell_price(item, stats)
+                    )
+                })
+                .collect::<Vec<_>>(),
+        };
+
+        let heading = match list.0 {
+            ShopMode::Buy => "Buy",
+            ShopMode::Sell => "Sell",
+        };
+        text.sections = list_sections(
+            &font.0,
+            heading,
+            list.0 == shop.mode,
+            shop.selected,
+            entries.into_iter().map(|entry| (entry, true)),
+        );
+    }
+}
+
+fn stat_summary((gun, bullet, armor, potion, potion_type): ItemStats<'_>) -> String {
+    if let (Some(gun), Some(bullet)) = (gun, bullet) {
+        return format!(", {:.0} DPS", WeaponSummary::new(gun, bullet).dps);
+    }
+    if let Some(armor) = armor {
+        return format!(", {} DEF", armor.defense);
+    }
+    if let (Some(potion), Some(potion_type)) = (potion, potion_type) {
+        return format!(", {}", potion_type.description(potion));
+    }
+    String::new()
+}
@@ -0,0 +1,61 @@
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+/// Uniform grid that buckets item indices by the cell their position falls in.
+pub struct SpatialHash {
+    cell_size: f32,
+    cells: HashMap<IVec2, Vec<usize>>,
+}
+
+impl SpatialHash {
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size,
+            cells: HashMap::default(),
+        }
+    }
+
+    pub fn cell_of(&self, pos: Vec2) -> IVec2 {
+        (pos / self.cell_size).floor().as_ivec2()
+    }
+
+    pub fn insert(&mut self, index: usize, pos: Vec2) {
+        let cell = self.cell_of(pos);
+        self.cells.entry(cell).or_default().push(index);
+    }
+
+    /// Inserts `index` into every cell overlapped by the box.
+    pub fn insert_aabb(&mut self, index: usize, min: Vec2, max: Vec2) {
+        let (min_cell, max_cell) = (self.cell_of(min), self.cell_of(max));
+        for y in min_cell.y..=max_cell.y {
+            for x in min_cell.x..=max_cell.x {
+                self.cells.entry(IVec2::new(x, y)).or_default().push(index);
+            }
+        }
+    }
+
+    /// Indices in every cell overlapped by the box. An index can appear more
+    /// than once if it was inserted with `insert_aabb`.
+    pub fn query_aabb(&self, min: Vec2, max: Vec2) -> impl Iterator<Item = usize> + '_ {
+        let (min_cell, max_cell) = (self.cell_of(min), self.cell_of(max));
+        (min_cell.y..=max_cell.y)
+            .flat_map(move |y| (min_cell.x..=max_cell.x).map(move |x| IVec2::new(x, y)))
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .copied()
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.clear();
+    }
+
+    /// Indices in the cell containing `pos` and its eight neighbours.
+    pub fn neighbours(&self, pos: Vec2) -> impl Iterator<Item = usize> + '_ {
+        let center = self.cell_of(pos);
+        (-1..=1)
+            .flat_map(move |dy| (-1..=1).map(move |dx| center + IVec2::new(dx, dy)))
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .copied()
+    }
+}
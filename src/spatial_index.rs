@@ -0,0 +1,227 @@
+use bevy::prelude::*;
+use bevy::utils::{HashMap, HashSet};
+
+use crate::ENEMY_INDEX_CELL_SIZE;
+
+/// Grid of enemy positions that is patched in place whenever an enemy moves,
+/// instead of being rebuilt from scratch.
+#[derive(Resource)]
+pub struct EnemyIndex {
+    cell_size: f32,
+    cells: HashMap<IVec2, Vec<Entity>>,
+    entries: HashMap<Entity, (IVec2, Vec2)>,
+}
+
+impl Default for EnemyIndex {
+    fn default() -> Self {
+        Self::new(ENEMY_INDEX_CELL_SIZE)
+    }
+}
+
+impl EnemyIndex {
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size,
+            cells: HashMap::default(),
+            entries: HashMap::default(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.clear();
+        self.entries.clear();
+    }
+
+    pub fn position(&self, entity: Entity) -> Option<Vec2> {
+        self.entries.get(&entity).map(|(_, pos)| *pos)
+    }
+
+    fn cell_of(&self, pos: Vec2) -> IVec2 {
+        (pos / self.cell_size).floor().as_ivec2()
+    }
+
+    /// Inserts the entity, or moves it if it is already indexed.
+    pub fn update(&mut self, entity: Entity, pos: Vec2) {
+        let cell = self.cell_of(pos);
+        match self.entries.get_mut(&entity) {
+            Some((old_cell, old_pos)) => {
+                *old_pos = pos;
+                if *old_cell != cell {
+                    Self::remove_from_cell(&mut self.cells, *old_cell, entity);
+                    self.cells.entry(cell).or_default().push(entity);
+                    *old_cell = cell;
+                }
+            }
+            None => {
+                self.entries.insert(entity, (cell, pos));
+                self.cells.entry(cell).or_default().push(entity);
+            }
+        }
+    }
+
+    pub fn remove(&mut self, entity: Entity) {
+        if let Some((cell, _)) = self.entries.remove(&entity) {
+            Self::remove_from_cell(&mut self.cells, cell, entity);
+        }
+    }
+
+    fn remove_from_cell(cells: &mut HashMap<IVec2, Vec<Entity>>, cell: IVec2, entity: Entity) {
+        if let Some(bucket) = cells.get_mut(&cell) {
+            if let Some(index) = bucket.iter().position(|e| *e == entity) {
+                bucket.swap_remove(index);
+            }
+            if bucket.is_empty() {
+                cells.remove(&cell);
+            }
+        }
+    }
+
+    fn entities_in(&self, cell: IVec2) -> impl Iterator<Item = (Entity, Vec2)> + '_ {
+        self.cells
+            .get(&cell)
+            .into_iter()
+            .flatten()
+            .map(|entity| (*entity, self.entries[entity].1))
+    }
+
+    /// Enemies whose position is within `radius` of `pos`.
+    pub fn within_radius(&self, pos: Vec2, radius: f32) -> Vec<(Entity, Vec2)> {
+        let min_cell = self.cell_of(pos - Vec2::splat(radius));
+        let max_cell = self.cell_of(pos + Vec2::splat(radius));
+        let radius_squared = radius * radius;
+
+        let mut found = Vec::new();
+        for y in min_cell.y..=max_cell.y {
+            for x in min_cell.x..=max_cell.x {
+                found.extend(
+                    self.entities_in(IVec2::new(x, y))
+                        .filter(|(_, other)| other.distance_squared(pos) <= radius_squared),
+                );
+            }
+        }
+        found
+    }
+
+    /// Up to `count` enemies closest to `pos`, nearest first.
+    pub fn nearest(&self, pos: Vec2, count: usize) -> Vec<(Entity, Vec2)> {
+        let mut found = Vec::new();
+        if count == 0 {
+            return found;
+        }
+
+        let center = self.cell_of(pos);
+        let mut seen = 0;
+        let mut ring = 0;
+        while seen < self.len() {
+            for cell in ring_cells(center, ring) {
+                for entry in self.entities_in(cell) {
+                    seen += 1;
+                    found.push(entry);
+                }
+            }
+            found.sort_by(|a, b| {
+                a.1.distance_squared(pos)
+                    .total_cmp(&b.1.distance_squared(pos))
+            });
+            found.truncate(count);
+
+            // Nothing in the next ring can be closer than `ring` whole cells
+            if found.len() == count
+                && found[count - 1].1.distance(pos) <= ring as f32 * self.cell_size
+            {
+                break;
+            }
+            ring += 1;
+        }
+        found
+    }
+
+    /// First enemy hit by a ray from `origin`, treating each enemy as a
+    /// circle of `radius`. Returns the entity and the distance along the ray.
+    /// `radius` should not exceed the cell size.
+    pub fn ray_cast(
+        &self,
+        origin: Vec2,
+        direction: Vec2,
+        max_distance: f32,
+        radius: f32,
+    ) -> Option<(Entity, f32)> {
+        let direction = direction.try_normalize()?;
+
+        let mut cells = HashSet::new();
+        let steps = (max_distance / self.cell_size).ceil() as i32;
+        for step in 0..=steps {
+            let sample = origin + direction * (step as f32 * self.cell_size).min(max_distance);
+            let center = self.cell_of(sample);
+            for dy in -1..=1 {
+                for dx in -1..=1 {
+                    cells.insert(center + IVec2::new(dx, dy));
+                }
+            }
+        }
+
+        let mut hit: Option<(Entity, f32)> = None;
+        for cell in cells {
+            for (entity, pos) in self.entities_in(cell) {
+                let along = (pos - origin).dot(direction);
+                let closest = origin + direction * along.clamp(0.0, max_distance);
+                let offset_squared = closest.distance_squared(pos);
+                if offset_squared > radius * radius {
+                    continue;
+                }
+                let distance = (along - (radius * radius - offset_squared).sqrt()).max(0.0);
+                if distance <= max_distance && hit.map_or(true, |(_, best)| distance < best) {
+                    hit = Some((entity, distance));
+                }
+            }
+        }
+        hit
+    }
+
+    /// Enemies within `range` of `origin` and no more than `half_angle`
+    /// radians away from `direction`.
+    pub fn cone(
+        &self,
+        origin: Vec2,
+        direction: Vec2,
+        half_angle: f32,
+        range: f32,
+    ) -> Vec<(Entity, Vec2)> {
+        let Some(direction) = direction.try_normalize() else {
+            return Vec::new();
+        };
+        let min_dot = half_angle.cos();
+        self.within_radius(origin, range)
+            .into_iter()
+            .filter(|(_, pos)| {
+                (*pos - origin)
+                    .try_normalize()
+                    .map_or(true, |to_enemy| to_enemy.dot(direction) >= min_dot)
+            })
+            .collect()
+    }
+}
+
+fn ring_cells(center: IVec2, ring: i32) -> Vec<IVec2> {
+    if ring == 0 {
+        return vec![center];
+    }
+    let mut cells = Vec::with_capacity(8 * ring as usize);
+    for d in -ring..=ring {
+        cells.push(center + IVec2::new(d, -ring));
+        cells.push(center + IVec2::new(d, ring));
+    }
+    for d in (-ring + 1)..ring {
+        cells.push(center + IVec2::new(-ring, d));
+        cells.push(center + IVec2::new(ring, d));
+    }
+    cells
+}
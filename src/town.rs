@@ -0,0 +1,306 @@
+use bevy::prelude::*;
+
+use crate::crafting::{handle_workbench_input, Workbench};
+use crate::dialog::{ActiveDialog, ShowDialogEvent};
+use crate::inventory::PlayerInventory;
+use crate::player::{handle_player_input, Player};
+use crate::resources::Wave;
+use crate::shop::{generate_stock, handle_shop_input, Merchant};
+use crate::state::GameState;
+use crate::world::InGameEntity;
+use crate::*;
+
+pub struct TownPlugin;
+
+/// Entities that only exist while the player is in town.
+#[derive(Component)]
+pub struct TownEntity;
+
+/// A townsperson the player can talk to. `dialog` names their script in
+/// `assets/dialogs`.
+#[derive(Component)]
+pub struct Npc {
+    pub name: String,
+    pub dialog: String,
+}
+
+#[derive(Component)]
+pub struct TownExitPortal;
+
+/// What entering the town changed in the run, so leaving can undo it.
+#[derive(Resource, Default)]
+struct TownVisit {
+    return_position: Vec3,
+    hidden: Vec<Entity>,
+}
+
+impl Plugin for TownPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TownVisit>()
+            .add_systems(OnEnter(GameState::Town), (enter_town, spawn_town).chain())
+            .add_systems(OnExit(GameState::Town), leave_town)
+            .add_systems(
+                Update,
+                (
+                    town_interaction
+                        .before(handle_shop_input)
+                        .before(handle_workbench_input),
+                    keep_player_in_town.after(handle_player_input),
+                )
+                    .run_if(in_state(GameState::Town)),
+            );
+    }
+}
+
+/// The town is built on top of the arena, so everything from the run except
+/// the player and their items is hidden while the player is there.
+#[allow(clippy::type_complexity)]
+fn enter_town(
+    mut player_query: Query<(&mut Transform, &PlayerInventory), With<Player>>,
+    mut world_query: Query<
+        (Entity, &mut Visibility),
+        (
+            With<InGameEntity>,
+            Without<Player>,
+            Without<Node>,
+            Without<TownEntity>,
+        ),
+    >,
+    mut visit: ResMut<TownVisit>,
+) {
+    let Ok((mut player_transform, inventory)) = player_query.get_single_mut() else {
+        return;
+    };
+    visit.return_position = player_transform.translation;
+    player_transform.translation = Vec3::new(0.0, TOWN_SPAWN_Y, player_transform.translation.z);
+
+    visit.hidden.clear();
+    for (entity, mut visibility) in world_query.iter_mut() {
+        if inventory.contains(entity) || *visibility == Visibility::Hidden {
+            continue;
+        }
+        *visibility = Visibility::Hidden;
+        visit.hidden.push(entity);
+    }
+}
+
+fn leave_town(
+    mut commands: Commands,
+    town_query: Query<Entity, With<TownEntity>>,
+    mut player_query: Query<&mut Transform, With<Player>>,
+    mut visibility_query: Query<&mut Visibility>,
+    mut visit: ResMut<TownVisit>,
+) {
+    for entity in town_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    if let Ok(mut player_transform) = player_query.get_single_mut() {
+        player_transform.translation = visit.return_position;
+    }
+    for entity in visit.hidden.drain(..) {
+        if let Ok(mut visibility) = visibility_query.get_mut(entity) {
+            *visibility = Visibility::Inherited;
+        }
+    }
+}
+
+fn spawn_town(
+    mut commands: Commands,
+    handle: Res<GlobalTextureAtlas>,
+    font: Res<UiFont>,
+    wave: Res<Wave>,
+) {
+    let exit_position = Vec2::new(0.0, TOWN_EXIT_Y);
+
+    // Ground
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::srgb(0.35, 0.3, 0.22),
+                custom_size: Some(Vec2::new(TOWN_W, TOWN_H)),
+                ..default()
+            },
+            transform: Transform::from_translation(Vec3::new(0.0, 0.0, 0.2)),
+            ..default()
+        },
+        TownEntity,
+        InGameEntity,
+    ));
+
+    // Houses along the top edge
+    for x in [-300.0, 0.0, 300.0] {
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::srgb(0.5, 0.3, 0.2),
+                    custom_size: Some(Vec2::new(160.0, 120.0)),
+                    ..default()
+                },
+                transform: Transform::from_translation(Vec3::new(x, 200.0, 0.3)),
+                ..default()
+            },
+            TownEntity,
+            InGameEntity,
+        ));
+    }
+
+    for (name, dialog, position, color) in [
+        (
+            "Merchant",
+            "merchant",
+            Vec2::new(-300.0, 100.0),
+            Color::srgb(1.0, 0.9, 0.4),
+        ),
+        (
+            "Blacksmith",
+            "blacksmith",
+            Vec2::new(0.0, 100.0),
+            Color::srgb(0.7, 0.7, 0.8),
+        ),
+        (
+            "Elder",
+            "elder",
+            Vec2::new(300.0, 100.0),
+            Color::srgb(0.6, 0.9, 0.6),
+        ),
+    ] {
+        let npc = commands
+            .spawn((
+                SpriteBundle {
+                    sprite: Sprite { color, ..default() },
+                    texture: handle.image.clone().unwrap(),
+                    transform: Transform::from_translation(position.extend(5.0))
+                        .with_scale(Vec3::splat(SPRITE_SCALE_FACTOR)),
+                    ..default()
+                },
+                TextureAtlas {
+                    layout: handle.layout.clone().unwrap(),
+                    index: 0,
+                },
+                Npc {
+                    name: name.to_string(),
+                    dialog: dialog.to_string(),
+                },
+                TownEntity,
+                InGameEntity,
+            ))
+            .id();
+        if name == "Merchant" {
+            let stock = generate_stock(&mut commands, &handle, wave.number);
+            commands.entity(npc).insert(Merchant { stock });
+        }
+        spawn_town_label(
+            &mut commands,
+            &font.0,
+            name,
+            position + Vec2::new(0.0, 45.0),
+        );
+    }
+
+    let workbench_position = Vec2::new(300.0, -120.0);
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::srgb(0.45, 0.3, 0.15),
+                custom_size: Some(Vec2::new(90.0, 40.0)),
+                ..default()
+            },
+            transform: Transform::from_translation(workbench_position.extend(1.0)),
+            ..default()
+        },
+        Npc {
+            name: "Workbench".to_string(),
+            dialog: "workbench".to_string(),
+        },
+        Workbench,
+        TownEntity,
+        InGameEntity,
+    ));
+    spawn_town_label(
+        &mut commands,
+        &font.0,
+        "Workbench",
+        workbench_position + Vec2::new(0.0, 40.0),
+    );
+
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::srgba(0.5, 0.0, 1.0, 0.5),
+                custom_size: Some(Vec2::new(50.0, 80.0)),
+                ..default()
+            },
+            transform: Transform::from_translation(exit_position.extend(1.0)),
+            ..default()
+        },
+        TownExitPortal,
+        TownEntity,
+        InGameEntity,
+    ));
+    spawn_town_label(
+        &mut commands,
+        &font.0,
+        "Exit",
+        exit_position + Vec2::new(0.0, 55.0),
+    );
+}
+
+fn spawn_town_label(commands: &mut Commands, font: &Handle<Font>, text: &str, position: Vec2) {
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section(
+                text,
+                TextStyle {
+                    font: font.clone(),
+                    font_size: 20.0,
+                    color: Color::WHITE,
+                },
+            ),
+            transform: Transform::from_translation(position.extend(20.0)),
+            ..default()
+        },
+        TownEntity,
+        InGameEntity,
+    ));
+}
+
+fn keep_player_in_town(mut player_query: Query<&mut Transform, With<Player>>) {
+    if let Ok(mut transform) = player_query.get_single_mut() {
+        transform.translation.x = transform.translation.x.clamp(-TOWN_W / 2.0, TOWN_W / 2.0);
+        transform.translation.y = transform.translation.y.clamp(-TOWN_H / 2.0, TOWN_H / 2.0);
+    }
+}
+
+/// X talks to the closest NPC in reach, or uses the exit portal.
+fn town_interaction(
+    player_query: Query<&Transform, With<Player>>,
+    npc_query: Query<(Entity, &Transform, &Npc)>,
+    portal_query: Query<&Transform, With<TownExitPortal>>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    active_dialog: Res<ActiveDialog>,
+    mut ev_show_dialog: EventWriter<ShowDialogEvent>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::KeyX) || active_dialog.0.is_some() {
+        return;
+    }
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+    let player_pos = player_transform.translation.truncate();
+    let distance = |transform: &Transform| transform.translation.truncate().distance(player_pos);
+
+    let closest_npc = npc_query
+        .iter()
+        .filter(|(_, transform, _)| distance(transform) <= TOWN_INTERACT_RADIUS)
+        .min_by(|(_, a, _), (_, b, _)| distance(a).total_cmp(&distance(b)));
+    if let Some((entity, _, npc)) = closest_npc {
+        ev_show_dialog.send(ShowDialogEvent::new(&npc.dialog).with_npc(entity));
+        return;
+    }
+    let near_portal = portal_query
+        .iter()
+        .any(|portal| distance(portal) <= TOWN_INTERACT_RADIUS);
+    if near_portal {
+        ev_show_dialog.send(ShowDialogEvent::new("town_exit"));
+    }
+}
@@ -0,0 +1,184 @@
+use bevy::prelude::*;
+
+use crate::dialog::ActiveDialog;
+use crate::town::TownEntity;
+
+/// The two lists of a town screen, switched between with Left/Right.
+pub trait TownScreenMode: Copy + PartialEq {
+    fn other(self) -> Self;
+}
+
+/// A spawned town screen. Callers tag `title` and `columns` with their own
+/// markers to fill them in.
+pub struct TownScreenLayout {
+    pub root: Entity,
+    pub title: Entity,
+    pub columns: Vec<Entity>,
+}
+
+/// Spawns a full screen town menu: a title, `columns` text columns side by
+/// side and a key hint underneath.
+pub fn spawn_town_screen(
+    commands: &mut Commands,
+    font: &Handle<Font>,
+    column_width: f32,
+    columns: usize,
+    hint: &str,
+) -> TownScreenLayout {
+    let text_style = |font_size: f32| TextStyle {
+        font: font.clone(),
+        font_size,
+        color: Color::WHITE,
+    };
+    let column = || NodeBundle {
+        style: Style {
+            width: Val::Px(column_width),
+            min_height: Val::Px(260.0),
+            flex_direction: FlexDirection::Column,
+            padding: UiRect::all(Val::Px(10.0)),
+            ..default()
+        },
+        background_color: Color::srgba(0.15, 0.15, 0.15, 0.9).into(),
+        ..default()
+    };
+
+    let title = commands
+        .spawn(TextBundle::from_section("", text_style(28.0)))
+        .id();
+    let column_texts = (0..columns)
+        .map(|_| {
+            commands
+                .spawn(TextBundle::from_section("", text_style(18.0)))
+                .id()
+        })
+        .collect::<Vec<_>>();
+    let row = commands
+        .spawn(NodeBundle {
+            style: Style {
+                column_gap: Val::Px(20.0),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            for text in column_texts.iter() {
+                parent.spawn(column()).add_child(*text);
+            }
+        })
+        .id();
+    let hint = commands
+        .spawn(TextBundle::from_section(hint, text_style(16.0)))
+        .id();
+
+    let root = commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    position_type: PositionType::Absolute,
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(10.0),
+                    ..default()
+                },
+                background_color: Color::srgba(0.0, 0.0, 0.0, 0.6).into(),
+                z_index: ZIndex::Global(10),
+                ..default()
+            },
+            TownEntity,
+        ))
+        .push_children(&[title, row, hint])
+        .id();
+
+    TownScreenLayout {
+        root,
+        title,
+        columns: column_texts,
+    }
+}
+
+/// Esc or X leaves any town screen.
+pub fn town_screen_closed(keyboard_input: &ButtonInput<KeyCode>) -> bool {
+    keyboard_input.just_pressed(KeyCode::Escape) || keyboard_input.just_pressed(KeyCode::KeyX)
+}
+
+/// Despawns the open town screen, hands `ActiveDialog` back and resets the
+/// screen's `state`.
+pub fn close_town_screen<S: Default>(
+    commands: &mut Commands,
+    state: &mut S,
+    active_dialog: &mut ActiveDialog,
+) {
+    if let Some(entity) = active_dialog.0.take() {
+        commands.entity(entity).despawn_recursive();
+    }
+    *state = S::default();
+}
+
+/// Left/Right switches list and Up/Down moves within it, `len` giving the
+/// length of each list. Returns the selected entry when Enter is pressed.
+pub fn navigate_town_screen<M: TownScreenMode>(
+    keyboard_input: &ButtonInput<KeyCode>,
+    mode: &mut M,
+    selected: &mut usize,
+    len: impl Fn(M) -> usize,
+) -> Option<usize> {
+    if keyboard_input.just_pressed(KeyCode::ArrowLeft)
+        || keyboard_input.just_pressed(KeyCode::ArrowRight)
+    {
+        *mode = mode.other();
+        *selected = 0;
+    }
+    let len = len(*mode);
+    if keyboard_input.just_pressed(KeyCode::ArrowUp) {
+        *selected = selected.saturating_sub(1);
+    }
+    if keyboard_input.just_pressed(KeyCode::ArrowDown) {
+        *selected += 1;
+    }
+    *selected = (*selected).min(len.saturating_sub(1));
+
+    (keyboard_input.just_pressed(KeyCode::Enter) && len > 0).then_some(*selected)
+}
+
+/// The text of one list: its heading, green while the list is active, then
+/// each entry. Unavailable entries are greyed out and the selection is
+/// highlighted.
+pub fn list_sections(
+    font: &Handle<Font>,
+    heading: &str,
+    active: bool,
+    selected: usize,
+    entries: impl IntoIterator<Item = (String, bool)>,
+) -> Vec<TextSection> {
+    let style = |color: Color| TextStyle {
+        font: font.clone(),
+        font_size: 18.0,
+        color,
+    };
+    let grey = Color::srgb(0.6, 0.6, 0.6);
+    let mut sections = vec![TextSection::new(
+        format!("{}\n", heading),
+        style(if active {
+            Color::srgb(0.0, 0.8, 0.0)
+        } else {
+            Color::WHITE
+        }),
+    )];
+    for (i, (entry, available)) in entries.into_iter().enumerate() {
+        let color = if active && i == selected {
+            Color::srgb(1.0, 0.8, 0.2)
+        } else if available {
+            Color::WHITE
+        } else {
+            grey
+        };
+        sections.push(TextSection::new(format!("{}\n", entry), style(color)));
+    }
+    if sections.len() == 1 {
+        sections.push(TextSection::new("Nothing", style(grey)));
+    }
+    sections
+}
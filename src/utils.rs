@@ -1,13 +1,42 @@
 use bevy::prelude::*;
 use rand::Rng;
 
-use crate::{WORLD_H, WORLD_W};
+use crate::{
+    ARMOR_DAMAGE_PER_DURABILITY, BOSS_COIN_MULTIPLIER, COIN_BASE_DROP, COIN_DROP_PER_WAVE,
+    ELITE_BASE_CHANCE, ELITE_CHANCE_PER_WAVE, ELITE_COIN_MULTIPLIER, ELITE_LOOT_MULTIPLIER,
+    ELITE_MAX_CHANCE, LOOT_CHANCE_PER_WAVE, WORLD_H, WORLD_W,
+};
 pub fn calculate_enemies_per_wave(wave_number: u32) -> u32 {
     let base_enemies = 10;
     let increase = (wave_number as f32 * 0.5).floor() as u32 * 3;
     base_enemies + increase
 }
 
+pub fn calculate_elite_chance(wave_number: u32) -> f32 {
+    (ELITE_BASE_CHANCE + ELITE_CHANCE_PER_WAVE * wave_number as f32).min(ELITE_MAX_CHANCE)
+}
+
+pub fn calculate_loot_chance_multiplier(wave_number: u32, elite: bool) -> f32 {
+    let multiplier = 1.0 + LOOT_CHANCE_PER_WAVE * wave_number.saturating_sub(1) as f32;
+    if elite {
+        multiplier * ELITE_LOOT_MULTIPLIER
+    } else {
+        multiplier
+    }
+}
+
+pub fn calculate_coin_drop(wave_number: u32, elite: bool, boss: bool) -> u32 {
+    let mut coins =
+        COIN_BASE_DROP + (COIN_DROP_PER_WAVE * wave_number.saturating_sub(1) as f32) as u32;
+    if elite {
+        coins *= ELITE_COIN_MULTIPLIER;
+    }
+    if boss {
+        coins *= BOSS_COIN_MULTIPLIER;
+    }
+    coins
+}
+
 pub fn calculate_health_increase(level: u32) -> u32 {
     let base_health_increase = 10.0;
     let exponential_factor: f32 = 1.05;
@@ -32,6 +61,12 @@ pub fn calculate_defense_increase(level: u32) -> u32 {
     defense_increase.round() as u32
 }
 
+/// Durability a piece loses to a hit: one point, plus one for every
+/// `ARMOR_DAMAGE_PER_DURABILITY` damage the hit dealt before defense.
+pub fn calculate_durability_loss(damage: u32) -> u32 {
+    1 + damage / ARMOR_DAMAGE_PER_DURABILITY
+}
+
 pub fn get_random_position_around(pos: Vec2) -> (f32, f32) {
     let mut rng = rand::thread_rng();
     let angle = rng.gen_range(0.0..std::f32::consts::TAU);
@@ -50,7 +85,7 @@ pub fn get_random_position_around(pos: Vec2) -> (f32, f32) {
 }
 
 pub fn safe_subtract(a: u32, b: u32) -> u32 {
-    a.checked_sub(b).unwrap_or(0)
+    a.saturating_sub(b)
 }
 
 pub fn clamp_position(position: &mut Vec3) {
@@ -0,0 +1,158 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::collision::{detect_collisions, PlayerTouchedPickup};
+use crate::pickup::Pickup;
+use crate::state::GameState;
+use crate::world::InGameEntity;
+use crate::*;
+
+pub struct WalletPlugin;
+
+/// Coins the player has this run. Only `apply_wallet_actions` changes the
+/// balance; everything else sends a `WalletAction`.
+#[derive(Resource, Default)]
+pub struct Wallet {
+    pub coins: u32,
+}
+
+/// A coin lying on the ground and how many coins it is worth.
+#[derive(Component)]
+pub struct Coin(pub u32);
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CoinReason {
+    Pickup,
+    RunEnded,
+    Purchase,
+    Sale,
+    Dialog,
+    Repair,
+}
+
+/// A request to change the balance. Spending more than the balance is
+/// refused and produces no `WalletChangedEvent`.
+#[derive(Event, Clone, Copy, Debug)]
+pub enum WalletAction {
+    Earn { amount: u32, reason: CoinReason },
+    Spend { amount: u32, reason: CoinReason },
+}
+
+/// Sent after every change to the balance.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct WalletChangedEvent {
+    pub balance: u32,
+    pub delta: i64,
+    pub reason: CoinReason,
+}
+
+impl Plugin for WalletPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Wallet>()
+            .add_event::<WalletAction>()
+            .add_event::<WalletChangedEvent>()
+            .add_systems(OnEnter(GameState::GameInit), carry_over_coins)
+            .add_systems(Update, apply_wallet_actions)
+            .add_systems(
+                Update,
+                collect_coins
+                    .after(detect_collisions)
+                    .before(apply_wallet_actions)
+                    .run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+fn apply_wallet_actions(
+    mut wallet: ResMut<Wallet>,
+    mut actions: EventReader<WalletAction>,
+    mut ev_changed: EventWriter<WalletChangedEvent>,
+) {
+    for action in actions.read() {
+        let (delta, reason) = match *action {
+            WalletAction::Earn { amount, reason } => (amount as i64, reason),
+            WalletAction::Spend { amount, reason } => {
+                if amount > wallet.coins {
+                    continue;
+                }
+                (-(amount as i64), reason)
+            }
+        };
+        if delta == 0 {
+            continue;
+        }
+        wallet.coins = (wallet.coins as i64 + delta) as u32;
+        ev_changed.send(WalletChangedEvent {
+            balance: wallet.coins,
+            delta,
+            reason,
+        });
+    }
+}
+
+/// Keeps `settings.coin_carry_over` of the balance when a new run starts.
+fn carry_over_coins(
+    wallet: Res<Wallet>,
+    settings: Res<Settings>,
+    mut ev_wallet: EventWriter<WalletAction>,
+) {
+    let kept = (wallet.coins as f32 * settings.coin_carry_over.clamp(0.0, 1.0)) as u32;
+    ev_wallet.send(WalletAction::Spend {
+        amount: wallet.coins - kept,
+        reason: CoinReason::RunEnded,
+    });
+}
+
+fn collect_coins(
+    mut commands: Commands,
+    coin_query: Query<&Coin>,
+    mut ev_touched: EventReader<PlayerTouchedPickup>,
+    mut ev_wallet: EventWriter<WalletAction>,
+) {
+    let mut collected = Vec::new();
+    for touched in ev_touched.read() {
+        let coin_entity = touched.pickup;
+        let Ok(coin) = coin_query.get(coin_entity) else {
+            continue;
+        };
+        if collected.contains(&coin_entity) {
+            continue;
+        }
+        collected.push(coin_entity);
+        ev_wallet.send(WalletAction::Earn {
+            amount: coin.0,
+            reason: CoinReason::Pickup,
+        });
+        commands.entity(coin_entity).despawn();
+    }
+}
+
+/// Scatters `total` coins around `position`, split over a few pickups.
+pub fn spawn_coins(commands: &mut Commands, position: Vec3, total: u32) {
+    if total == 0 {
+        return;
+    }
+    let mut rng = rand::thread_rng();
+    let count = total.min(MAX_COINS_PER_DROP);
+    for i in 0..count {
+        // Spread the remainder over the first few coins
+        let value = total / count + u32::from(i < total % count);
+        let offset = Vec2::new(rng.gen_range(-25.0..25.0), rng.gen_range(-25.0..25.0));
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::srgb(1.0, 0.85, 0.1),
+                    custom_size: Some(Vec2::splat(COIN_SIZE)),
+                    ..default()
+                },
+                transform: Transform::from_translation(
+                    (position.truncate() + offset).extend(LOOT_Z),
+                ),
+                ..default()
+            },
+            Coin(value),
+            Pickup::collider(),
+            InGameEntity,
+        ));
+    }
+}
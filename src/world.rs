@@ -1,12 +1,17 @@
 use armor::*;
 use bevy::math::vec3;
 use bevy::prelude::*;
+use effects::{ActiveEffects, BaseStats, BonusHealth};
 use gun::{BulletStats, GunBundle, GunStats};
-use player::{Defense, PlayerInventory, Speed};
+use hotbar::HotbarCooldowns;
+use inventory::{ArmorSlot, Item, PlayerInventory};
+use knockback::{Knockback, Mass};
+use player::{Defense, Speed};
 use potion::{Potion, PotionBundle, PotionStats, PotionType};
 use rand::Rng;
 
 use crate::animation::AnimationTimer;
+use crate::collision::{Collider, CollisionLayer};
 use crate::gun::GunType;
 use crate::player::{Health, Player, PlayerState};
 use crate::*;
@@ -17,11 +22,14 @@ pub struct WorldPlugin;
 #[derive(Component)]
 pub struct InGameEntity; //entities that spawn with this will be cleared after each game run
 
+#[derive(Component)]
+pub struct Obstacle;
+
 impl Plugin for WorldPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
             OnEnter(GameState::GameInit),
-            (init_world, spawn_world_decorations),
+            (init_world, spawn_world_decorations, spawn_obstacles),
         )
         .add_systems(OnExit(GameState::InGame), despawn_all_game_entities);
     }
@@ -50,6 +58,18 @@ fn init_world(
             Health(PLAYER_HEALTH),
             Speed(PLAYER_SPEED),
             Defense(1),
+            (
+                BaseStats {
+                    speed: PLAYER_SPEED,
+                    defense: 1,
+                },
+                ActiveEffects::default(),
+                BonusHealth::default(),
+            ),
+            HotbarCooldowns::default(),
+            Mass(PLAYER_MASS),
+            Knockback::default(),
+            Collider::circle(PLAYER_COLLIDER_RADIUS, CollisionLayer::Player),
             PlayerState::default(),
             AnimationTimer(Timer::from_seconds(0.15, TimerMode::Repeating)),
             InGameEntity,
@@ -85,6 +105,7 @@ fn init_world(
                     ..default()
                 },
                 gun_type: GunType::Gun1,
+                item: Item::weapon("Scattergun"),
                 gun_stats: GunStats {
                     bullets_per_shot: 20,
                     firing_interval: 0.1,
@@ -118,6 +139,7 @@ fn init_world(
                     effect_amount: 10,
                 },
                 potion_type: PotionType::Health,
+                item: Item::consumable("Health Potion"),
                 in_game_entity: InGameEntity,
             },
             TextureAtlas {
@@ -141,6 +163,7 @@ fn init_world(
                     effect_amount: 10,
                 },
                 potion_type: PotionType::Speed,
+                item: Item::consumable("Speed Potion"),
                 in_game_entity: InGameEntity,
             },
             TextureAtlas {
@@ -150,17 +173,20 @@ fn init_world(
         ))
         .id();
 
-    // Add both guns to the player's inventory
     let armor1 = commands
         .spawn((
             ArmorBundle {
                 armor: Armor,
-                armor_stats: ArmorStats {
-                    defense: 2,
-                    durability: 20,
-                },
+                item: Item::armor("Leather Armor", ArmorSlot::Body),
+                armor_stats: ArmorStats::new(ArmorSlot::Body, 2, 20).in_set("Leather"),
                 in_game_entity: InGameEntity,
             },
+            SpriteBundle {
+                texture: handle.image.clone().unwrap(),
+                transform: Transform::from_scale(Vec3::splat(SPRITE_SCALE_FACTOR)),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
             TextureAtlas {
                 layout: handle.layout.clone().unwrap(),
                 index: 58,
@@ -172,12 +198,16 @@ fn init_world(
         .spawn((
             ArmorBundle {
                 armor: Armor,
-                armor_stats: ArmorStats {
-                    defense: 3,
-                    durability: 30,
-                },
+                item: Item::armor("Chain Armor", ArmorSlot::Body),
+                armor_stats: ArmorStats::new(ArmorSlot::Body, 3, 30).in_set("Chain"),
                 in_game_entity: InGameEntity,
             },
+            SpriteBundle {
+                texture: handle.image.clone().unwrap(),
+                transform: Transform::from_scale(Vec3::splat(SPRITE_SCALE_FACTOR)),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
             TextureAtlas {
                 layout: handle.layout.clone().unwrap(),
                 index: 59,
@@ -185,15 +215,18 @@ fn init_world(
         ))
         .id();
 
-    // Add guns, potions, and armors to the player's inventory
-    commands.entity(player_entity).insert(PlayerInventory {
-        guns: vec![gun1, gun2],
-        active_gun_index: 0,
-        health_potions: vec![potion1],
-        speed_potions: vec![potion2],
-        armors: vec![armor1, armor2],
-        active_armor_index: 0,
-    });
+    // Equip the first gun and armor, carry the spares in the bag and put the
+    // potions on the quick-use bar
+    let mut inventory = PlayerInventory {
+        weapon: Some(gun1),
+        ..default()
+    };
+    inventory.armor[ArmorSlot::Body.index()] = Some(armor1);
+    inventory.quick_use[0] = Some(potion1);
+    inventory.quick_use[1] = Some(potion2);
+    inventory.bag[0] = Some(gun2);
+    inventory.bag[1] = Some(armor2);
+    commands.entity(player_entity).insert(inventory);
 
     next_state.set(GameState::InGame);
 }
@@ -219,6 +252,40 @@ fn spawn_world_decorations(mut commands: Commands, handle: Res<GlobalTextureAtla
     }
 }
 
+fn spawn_obstacles(mut commands: Commands) {
+    let mut rng = rand::thread_rng();
+    for _ in 0..NUM_OBSTACLES {
+        let size = Vec2::new(
+            rng.gen_range(OBSTACLE_MIN_SIZE..OBSTACLE_MAX_SIZE),
+            rng.gen_range(OBSTACLE_MIN_SIZE..OBSTACLE_MAX_SIZE),
+        );
+        // Keep the player's spawn point clear
+        let position = loop {
+            let candidate = Vec2::new(
+                rng.gen_range(-WORLD_W..WORLD_W),
+                rng.gen_range(-WORLD_H..WORLD_H),
+            );
+            if candidate.length() > OBSTACLE_SPAWN_CLEARANCE {
+                break candidate;
+            }
+        };
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::srgb(0.3, 0.3, 0.35),
+                    custom_size: Some(size),
+                    ..default()
+                },
+                transform: Transform::from_translation(position.extend(0.5)),
+                ..default()
+            },
+            Obstacle,
+            Collider::aabb(size / 2.0, CollisionLayer::Obstacle),
+            InGameEntity,
+        ));
+    }
+}
+
 fn despawn_all_game_entities(
     mut commands: Commands,
     all_entities: Query<Entity, With<InGameEntity>>,